@@ -0,0 +1,68 @@
+use arrow_array::types::{Int32Type, Int64Type};
+use arrow_array::{Array, Int32Array, Int64Array};
+use encoding_rust::delta_encoding_64::{
+    DeltaVec, Encoder, NullableDecoder, NullableEncoder, SliceDecoder,
+};
+
+fn main() {
+    let array = Int64Array::from_iter_values((0..1000).map(|i| 1_600_000_000_000 + i * 1000));
+    let mut encoder = Encoder::new(1);
+    encoder.write_array(&array).unwrap();
+    let bytes = encoder.finish().unwrap();
+    let mut decoder = SliceDecoder::new(&bytes).unwrap();
+    assert_eq!(decoder.read_array::<Int64Type>().unwrap(), array);
+
+    let array = Int32Array::from_iter_values(-500..500);
+    encoder.write_array(&array).unwrap();
+    let bytes = encoder.finish().unwrap();
+    let mut decoder = SliceDecoder::new(&bytes).unwrap();
+    let mut head = [0; 10];
+    assert_eq!(decoder.read_into(&mut head).unwrap(), 10);
+    assert_eq!(
+        head,
+        [-500, -499, -498, -497, -496, -495, -494, -493, -492, -491]
+    );
+    let tail = decoder.read_array::<Int32Type>().unwrap();
+    assert_eq!(tail, array.slice(10, 990));
+
+    let mut decoder = SliceDecoder::new(&bytes).unwrap();
+    assert_eq!(decoder.read_array::<Int64Type>().unwrap().value(0), -500);
+
+    let encoded = Int64Array::from(vec![0, i64::from(i32::MAX) + 1]);
+    encoder.write_array(&encoded).unwrap();
+    let bytes = encoder.finish().unwrap();
+    let mut decoder = SliceDecoder::new(&bytes).unwrap();
+    assert!(decoder.read_array::<Int32Type>().is_err());
+
+    let with_nulls = Int32Array::from(
+        (0..300)
+            .map(|i| (i % 3 != 0).then_some(i * 7))
+            .collect::<Vec<_>>(),
+    );
+    assert!(encoder.write_array(&with_nulls).is_err());
+
+    let mut nullable = NullableEncoder::new(1);
+    nullable.write_array(&with_nulls).unwrap();
+    let mut buffer = vec![];
+    nullable.write(&mut buffer).unwrap();
+    let mut decoder = NullableDecoder::new(buffer.as_slice()).unwrap();
+    let decoded = decoder.read_array::<Int32Type>().unwrap();
+    assert_eq!(decoded, with_nulls);
+    assert_eq!(decoded.null_count(), 100);
+
+    let mut decoder = NullableDecoder::new(buffer.as_slice()).unwrap();
+    for _ in 0..5 {
+        decoder.read_integer().unwrap();
+    }
+    assert_eq!(
+        decoder.read_array::<Int32Type>().unwrap(),
+        with_nulls.slice(5, 295)
+    );
+
+    let array = Int64Array::from(vec![5, 3, 9, -1]);
+    let delta_vec = DeltaVec::from_array(&array).unwrap();
+    assert_eq!(delta_vec.to_vec(), vec![5, 3, 9, -1]);
+    assert_eq!(delta_vec.to_array().unwrap(), array);
+    assert_eq!(DeltaVec::new().to_array().unwrap().len(), 0);
+    assert!(DeltaVec::from_array(&Int64Array::from(vec![Some(1), None])).is_err());
+}
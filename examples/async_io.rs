@@ -0,0 +1,80 @@
+use encoding_rust::delta_encoding_64::{AsyncDecoder, AsyncEncoder, Encoder, Format, Mode};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+async fn round_trip(encoder: Encoder, values: &[i64], pipe_size: usize) {
+    let format = encoder.format();
+    let (writer, reader) = tokio::io::duplex(pipe_size);
+
+    let write = async {
+        let mut encoder = AsyncEncoder::new(encoder, writer);
+        for value in values {
+            encoder.write_integer(*value).unwrap();
+        }
+        encoder.write().await.unwrap();
+    };
+    let read = async {
+        let mut decoder = AsyncDecoder::with_format(reader, format).await.unwrap();
+        decoder.read_integers().await.unwrap()
+    };
+
+    let ((), decoded) = tokio::join!(write, read);
+    assert_eq!(decoded, values);
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(41);
+    let values: Vec<i64> = (0..10_000)
+        .map(|_| rng.gen_range(-1 << 40..1 << 40))
+        .collect();
+
+    for pipe_size in [1, 7, 64, 1 << 16] {
+        for len in [0, 1, 2, 65, 1000] {
+            round_trip(Encoder::new(1), &values[..len], pipe_size).await;
+        }
+        round_trip(Encoder::new(2), &values, pipe_size).await;
+        round_trip(
+            Encoder::new(1).with_mode(Mode::DeltaOfDelta),
+            &values,
+            pipe_size,
+        )
+        .await;
+        round_trip(
+            Encoder::new(2).with_block_index(true).with_stats(true),
+            &values,
+            pipe_size,
+        )
+        .await;
+        round_trip(
+            Encoder::new(2)
+                .with_mode(Mode::DeltaOfDelta)
+                .with_block_index(true)
+                .with_checksums(true),
+            &values,
+            pipe_size,
+        )
+        .await;
+        round_trip(
+            Encoder::new(2).with_format(Format::Parquet),
+            &values,
+            pipe_size,
+        )
+        .await;
+    }
+
+    let mut encoder = Encoder::new(1);
+    values.iter().for_each(|i| {
+        encoder.write_integer(*i).unwrap();
+    });
+    let bytes = encoder.finish().unwrap();
+    let mut decoder = AsyncDecoder::new(&bytes[..bytes.len() - 100])
+        .await
+        .unwrap();
+    assert!(decoder.read_integers().await.is_err());
+    assert!(AsyncDecoder::new(&bytes[..3]).await.is_err());
+
+    let mut decoder = AsyncDecoder::new(&bytes[..]).await.unwrap();
+    assert_eq!(decoder.read_integers().await.unwrap(), values);
+    assert!(decoder.read_integer().await.is_err());
+}
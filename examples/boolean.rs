@@ -0,0 +1,57 @@
+use encoding_rust::boolean;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn encode(values: &[bool]) -> Vec<u8> {
+    let mut encoder = boolean::Encoder::new();
+    values.iter().for_each(|value| {
+        encoder.write_value(*value).unwrap();
+    });
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    buffer
+}
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let random: Vec<bool> = (0..1000).map(|_| rng.gen_bool(0.5)).collect();
+    let buffer = encode(&random);
+    // Bit packed: encoding byte, two varints and one bit per value.
+    assert!(buffer.len() <= 3 + 2 + 1000 / 8 + 1);
+
+    let mut decoder = boolean::Decoder::new(buffer.as_slice()).unwrap();
+    assert_eq!(
+        decoder.bitmap.count_ones(),
+        random.iter().filter(|v| **v).count()
+    );
+    let ones: Vec<usize> = (0..random.len()).filter(|i| random[*i]).collect();
+    assert_eq!(decoder.bitmap.iter_ones().collect::<Vec<_>>(), ones);
+    assert_eq!(decoder.read_values().unwrap(), random);
+
+    let mut runs = vec![false; 5000];
+    runs.extend(vec![true; 3000]);
+    runs.push(false);
+    let buffer = encode(&runs);
+    assert!(buffer.len() < 16);
+
+    let mut decoder = boolean::Decoder::new(buffer.as_slice()).unwrap();
+    assert_eq!(decoder.bitmap.count_ones(), 3000);
+    assert_eq!(decoder.bitmap.iter_ones().next(), Some(5000));
+    assert_eq!(decoder.read_values().unwrap(), runs);
+
+    let mut decoder = boolean::Decoder::new(encode(&[]).as_slice()).unwrap();
+    assert!(decoder.read_values().unwrap().is_empty());
+
+    // Corrupted lengths are errors rather than panics or huge allocations.
+    let mut corrupted = buffer[..1].to_vec();
+    corrupted.extend_from_slice(&[0x7f; 12]);
+    assert!(boolean::Decoder::new(corrupted.as_slice()).is_err());
+    let mut corrupted = buffer[..1].to_vec();
+    corrupted.extend_from_slice(&[0x81, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x80]);
+    assert!(boolean::Decoder::new(corrupted.as_slice()).is_err());
+    let mut corrupted = buffer[..1].to_vec();
+    corrupted.extend_from_slice(&[0x7f, 0x7f, 0x7f, 0x7f, 0x80]);
+    // The payload of the runs, after its two byte count.
+    corrupted.extend_from_slice(&buffer[3..]);
+    assert!(boolean::Decoder::new(corrupted.as_slice()).is_err());
+}
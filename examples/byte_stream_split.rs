@@ -0,0 +1,54 @@
+use encoding_rust::byte_stream_split;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn main() {
+    // Example from the Parquet specification.
+    let values = [0xAA, 0xBB, 0xCC, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55];
+    let mut streams = [0u8; 9];
+    byte_stream_split::encode(&values, 3, &mut streams).unwrap();
+    assert_eq!(
+        streams,
+        [0xAA, 0x00, 0x33, 0xBB, 0x11, 0x44, 0xCC, 0x22, 0x55]
+    );
+    let mut decoded = [0u8; 9];
+    byte_stream_split::decode(&streams, 3, &mut decoded).unwrap();
+    assert_eq!(decoded, values);
+
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let prices: Vec<f64> = (0..1000).map(|_| rng.gen_range(99.0..101.0)).collect();
+
+    let mut encoder = byte_stream_split::Encoder::<f64>::new();
+    prices.iter().for_each(|price| encoder.write_value(*price));
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+
+    // The exponent bytes of similar prices end up in one constant stream.
+    let exponent_stream = &buffer[7 * prices.len()..];
+    assert!(exponent_stream
+        .iter()
+        .all(|byte| *byte == exponent_stream[0]));
+
+    let decoder = byte_stream_split::Decoder::<f64>::new(&buffer).unwrap();
+    assert_eq!(decoder.read_values().unwrap(), prices);
+
+    let amounts: Vec<i128> = (0..1000).map(|_| rng.gen()).collect();
+    let mut encoder = byte_stream_split::Encoder::<i128>::new();
+    amounts
+        .iter()
+        .for_each(|amount| encoder.write_value(*amount));
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    let decoder = byte_stream_split::Decoder::<i128>::new(&buffer).unwrap();
+    assert_eq!(decoder.read_values().unwrap(), amounts);
+
+    let sizes: Vec<f32> = (0..77).map(|_| rng.gen()).collect();
+    let mut encoder = byte_stream_split::Encoder::<f32>::new();
+    sizes.iter().for_each(|size| encoder.write_value(*size));
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    let decoder = byte_stream_split::Decoder::<f32>::new(&buffer).unwrap();
+    assert_eq!(decoder.read_values().unwrap(), sizes);
+
+    assert!(byte_stream_split::Decoder::<f32>::new(&buffer[1..]).is_err());
+}
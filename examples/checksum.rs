@@ -0,0 +1,156 @@
+use encoding_rust::delta_encoding_64::{
+    BytesDecoder, ChecksumMismatch, Decoder, Encoder, Format, Mode, SliceDecoder, StreamingEncoder,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::io::Cursor;
+
+fn mismatch(error: anyhow::Error) -> ChecksumMismatch {
+    *error
+        .downcast_ref::<ChecksumMismatch>()
+        .unwrap_or_else(|| panic!("Not a checksum mismatch: {}", error))
+}
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(45);
+    let values: Vec<i64> = (0..3_000).map(|_| rng.gen_range(0..1_000_000)).collect();
+
+    let encoders = [
+        Encoder::new(1),
+        Encoder::new(2).with_mode(Mode::DeltaOfDelta),
+        Encoder::new(4).with_block_index(true),
+        Encoder::new(1).with_stats(true).with_block_index(true),
+    ];
+    for encoder in encoders {
+        for len in [0, 1, 2, 3, 200, values.len()] {
+            let plain = encoder.clone().finish().unwrap();
+            let mut encoder = encoder.clone().with_checksums(true);
+            for value in &values[..len] {
+                encoder.write_integer(*value).unwrap();
+            }
+            let bytes = encoder.finish().unwrap();
+            assert!(bytes.len() > plain.len());
+
+            let mut decoder = SliceDecoder::new(&bytes).unwrap();
+            assert_eq!(decoder.read_integers().unwrap(), &values[..len]);
+            let mut decoder = Decoder::new(&bytes[..]).unwrap();
+            assert_eq!(decoder.read_integers().unwrap(), &values[..len]);
+            assert!(decoder.io.into_inner().is_empty());
+            let mut decoder = BytesDecoder::new(bytes.clone()).unwrap();
+            let range = 100_000..500_000;
+            let expected: Vec<usize> = (0..len).filter(|i| range.contains(&values[*i])).collect();
+            assert_eq!(decoder.filter_range(range.clone()).unwrap(), expected);
+            let mut decoder = SliceDecoder::from_input_unverified(&bytes, Format::Native).unwrap();
+            assert_eq!(decoder.filter_range(range).unwrap(), expected);
+        }
+    }
+
+    let mut encoder = Encoder::new(1).with_checksums(true);
+    for value in &values {
+        encoder.write_integer(*value).unwrap();
+    }
+    let bytes = encoder.finish().unwrap();
+
+    // Every flipped bit is caught, either as a checksum mismatch or as an invalid stream,
+    // before any value of the corrupted part is returned.
+    for position in 0..bytes.len() {
+        let mut corrupted = bytes.to_vec();
+        corrupted[position] ^= 1 << (position % 8);
+        let result = SliceDecoder::new(&corrupted).and_then(|mut decoder| decoder.read_integers());
+        assert!(result.is_err(), "Flipped bit at {} not detected", position);
+    }
+
+    // The header is covered by its own checksum, blocks by theirs.
+    let mut corrupted = bytes.to_vec();
+    corrupted[2] ^= 0x10;
+    assert_eq!(
+        mismatch(SliceDecoder::new(&corrupted).unwrap_err()).block,
+        None
+    );
+
+    let mut corrupted = bytes.to_vec();
+    // Within the last block, but before the padding after the last value.
+    let position = corrupted.len() - 40;
+    corrupted[position] ^= 0x10;
+    let mut decoder = SliceDecoder::new(&corrupted).unwrap();
+    let mut read = 0;
+    let error = loop {
+        match decoder.read_integer() {
+            Ok(value) => {
+                assert_eq!(value, values[read]);
+                read += 1;
+            }
+            Err(error) => break error,
+        }
+    };
+    let error = mismatch(error);
+    let blocks = (values.len() - 1).div_ceil(64);
+    assert_eq!(error.block, Some(blocks - 1));
+    assert_ne!(error.expected, error.actual);
+    assert_eq!(read, 1 + (blocks - 1) * 64);
+    assert!(error.to_string().starts_with("Checksum mismatch in block"));
+
+    // Skipping verification trusts the bytes as they are.
+    let mut decoder = SliceDecoder::from_input_unverified(&corrupted, Format::Native).unwrap();
+    let decoded = decoder.read_integers().unwrap();
+    assert_eq!(decoded[..read], values[..read]);
+    assert_ne!(decoded, values);
+    let mut decoder = SliceDecoder::from_input_unverified(&bytes, Format::Native).unwrap();
+    assert_eq!(decoder.read_integers().unwrap(), values);
+
+    // With whole blocks and so no padding, only the checksums themselves can be flipped
+    // without changing the unverified values.
+    let whole_blocks = &values[..1 + 40 * 64];
+    let mut encoder = Encoder::new(1).with_checksums(true);
+    for value in whole_blocks {
+        encoder.write_integer(*value).unwrap();
+    }
+    let whole_bytes = encoder.finish().unwrap();
+    let mut checksum_bytes = 0;
+    for position in 0..whole_bytes.len() {
+        let mut corrupted = whole_bytes.to_vec();
+        corrupted[position] ^= 1 << (position % 8);
+        let unverified = SliceDecoder::from_input_unverified(&corrupted, Format::Native)
+            .and_then(|mut decoder| decoder.read_integers());
+        if unverified.is_ok_and(|decoded| decoded == whole_blocks) {
+            let error = SliceDecoder::new(&corrupted)
+                .and_then(|mut decoder| decoder.read_integers())
+                .unwrap_err();
+            mismatch(error);
+            checksum_bytes += 1;
+        }
+    }
+    assert_eq!(checksum_bytes, 4 * (40 + 1));
+
+    // Streams without checksums still decode.
+    let mut encoder = Encoder::new(1);
+    for value in &values {
+        encoder.write_integer(*value).unwrap();
+    }
+    let plain = encoder.finish().unwrap();
+    assert_eq!(plain.len() + 4 * (blocks + 1), bytes.len());
+    assert_eq!(
+        SliceDecoder::new(&plain).unwrap().read_integers().unwrap(),
+        values
+    );
+
+    let mut streaming =
+        StreamingEncoder::new(Encoder::new(2).with_checksums(true), Cursor::new(vec![])).unwrap();
+    for value in &values {
+        streaming.write_integer(*value).unwrap();
+    }
+    let streamed = streaming.finish().unwrap().into_inner();
+    let mut decoder = SliceDecoder::new(&streamed).unwrap();
+    assert_eq!(decoder.read_integers().unwrap(), values);
+    let mut corrupted = streamed.clone();
+    corrupted[4] ^= 1;
+    assert!(mismatch(SliceDecoder::new(&corrupted).unwrap_err())
+        .block
+        .is_none());
+
+    let mut encoder = Encoder::new(2)
+        .with_format(Format::Parquet)
+        .with_checksums(true);
+    encoder.write_integer(1).unwrap();
+    assert!(encoder.finish().is_err());
+}
@@ -0,0 +1,113 @@
+use encoding_rust::boolean;
+use encoding_rust::container::{ContainerReader, ContainerWriter, Encoding, ValueType};
+use encoding_rust::delta_encoding_64::{Encoder, NullableDecoder, NullableEncoder};
+use std::io::Cursor;
+
+fn main() {
+    let timestamps: Vec<i64> = (0..10_000).map(|i| 1_600_000_000 + i * 15).collect();
+    let counts: Vec<Option<i64>> = (0..10_000)
+        .map(|i| (i % 7 != 0).then_some(i % 100))
+        .collect();
+    let flags: Vec<bool> = (0..10_000).map(|i| i % 3 == 0).collect();
+
+    let mut writer = ContainerWriter::new(Cursor::new(vec![])).unwrap();
+
+    let mut encoder = Encoder::new(4);
+    for value in &timestamps {
+        encoder.write_integer(*value).unwrap();
+    }
+    writer
+        .write_delta_column("timestamp", &mut encoder)
+        .unwrap();
+
+    let mut nullable = NullableEncoder::new(1);
+    for value in &counts {
+        nullable.write_integer(*value).unwrap();
+    }
+    let mut data = vec![];
+    nullable.write(&mut data).unwrap();
+    writer
+        .write_column("count", ValueType::Int64, Encoding::NullableDelta, &data)
+        .unwrap();
+
+    let mut encoder = boolean::Encoder::new();
+    for value in &flags {
+        encoder.write_value(*value).unwrap();
+    }
+    let mut data = vec![];
+    encoder.write(&mut data).unwrap();
+    writer
+        .write_column("flag", ValueType::Boolean, Encoding::Boolean, &data)
+        .unwrap();
+    writer
+        .write_column("empty", ValueType::Binary, Encoding::DeltaByteArray, &[])
+        .unwrap();
+
+    assert!(writer
+        .write_column("flag", ValueType::Boolean, Encoding::Boolean, &data)
+        .is_err());
+    let bytes = writer.finish().unwrap().into_inner();
+
+    let mut reader = ContainerReader::new(Cursor::new(bytes.clone())).unwrap();
+    let names: Vec<&str> = reader.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["timestamp", "count", "flag", "empty"]);
+    assert_eq!(
+        reader.column("flag").unwrap().value_type,
+        ValueType::Boolean
+    );
+    assert_eq!(reader.column("empty").unwrap().len, 0);
+    assert!(reader.column("missing").is_none());
+    assert!(reader.read_column("missing").is_err());
+
+    // Columns are read in any order, and only when asked for.
+    let data = reader.read_column("flag").unwrap();
+    let mut decoder = boolean::Decoder::new(&data[..]).unwrap();
+    assert_eq!(decoder.read_values().unwrap(), flags);
+
+    let data = reader.read_column("count").unwrap();
+    let mut decoder = NullableDecoder::new(&data[..]).unwrap();
+    assert_eq!(decoder.read_integers().unwrap(), counts);
+
+    let mut decoder = reader.delta_decoder("timestamp").unwrap();
+    assert_eq!(decoder.read_integers().unwrap(), timestamps);
+    assert!(reader.delta_decoder("count").is_err());
+    assert!(reader.read_column("empty").unwrap().is_empty());
+
+    // A flipped bit in a column is only noticed when that column is read.
+    let mut corrupted = bytes.clone();
+    let flag = reader.column("flag").unwrap().clone();
+    corrupted[flag.offset as usize + 3] ^= 1;
+    let mut reader = ContainerReader::new(Cursor::new(corrupted)).unwrap();
+    assert!(reader.read_column("flag").is_err());
+    assert!(reader.read_column("count").is_ok());
+
+    // Corrupting the directory fails on open.
+    let mut corrupted = bytes.clone();
+    let position = bytes.len() - 20;
+    corrupted[position] ^= 1;
+    assert!(ContainerReader::new(Cursor::new(corrupted)).is_err());
+
+    let mut corrupted = bytes.clone();
+    corrupted[0] = b'X';
+    assert!(ContainerReader::new(Cursor::new(corrupted)).is_err());
+    let mut corrupted = bytes.clone();
+    corrupted[4] = 2;
+    assert!(ContainerReader::new(Cursor::new(corrupted)).is_err());
+    for len in [0, 5, 20, bytes.len() - 1] {
+        assert!(ContainerReader::new(Cursor::new(&bytes[..len])).is_err());
+    }
+
+    let bytes = ContainerWriter::new(vec![]).unwrap().finish().unwrap();
+    assert_eq!(bytes.len(), 5 + 1 + 16);
+    let reader = ContainerReader::new(Cursor::new(bytes.clone())).unwrap();
+    assert!(reader.columns.is_empty());
+
+    // An over-long column count with a matching checksum is an error, not a panic.
+    let directory = [0x7f; 12];
+    let mut corrupted = bytes[..5].to_vec();
+    corrupted.extend_from_slice(&directory);
+    corrupted.extend_from_slice(&5u64.to_le_bytes());
+    corrupted.extend_from_slice(&crc32c::crc32c(&directory).to_le_bytes());
+    corrupted.extend_from_slice(&bytes[bytes.len() - 4..]);
+    assert!(ContainerReader::new(Cursor::new(corrupted)).is_err());
+}
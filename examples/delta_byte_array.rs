@@ -0,0 +1,63 @@
+use encoding_rust::delta_encoding_64::{Encoder, Format, StreamingEncoder};
+use encoding_rust::{delta_byte_array, delta_length_byte_array};
+
+fn main() {
+    let before: Vec<&[u8]> = vec![b"Hello", b"World", b"Foobar", b"ABCDEF"];
+
+    let mut encoder = delta_length_byte_array::Encoder::new();
+    before.iter().for_each(|value| {
+        encoder.write_value(value).unwrap();
+    });
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+
+    // Lengths 5, 5, 6, 6 as DELTA_BINARY_PACKED with ULEB128 varints, then the bytes.
+    let mut expected = vec![
+        0x80, 0x01, 0x02, 0x04, 0x0A, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ];
+    expected.extend_from_slice(b"HelloWorldFoobarABCDEF");
+    assert_eq!(buffer, expected);
+
+    let mut decoder = delta_length_byte_array::Decoder::new(&buffer).unwrap();
+    assert_eq!(decoder.read_values().unwrap(), before);
+
+    // Other writers use four mini blocks of 32 values per block.
+    let mut other_writer = vec![
+        0x80, 0x01, 0x04, 0x04, 0x0A, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+    ];
+    other_writer.extend_from_slice(b"HelloWorldFoobarABCDEF");
+    let mut decoder = delta_length_byte_array::Decoder::new(&other_writer).unwrap();
+    assert_eq!(decoder.read_values().unwrap(), before);
+
+    let before: Vec<Vec<u8>> = (0..1000)
+        .map(|i| format!("ticker/{:05}/price", i * 7).into_bytes())
+        .collect();
+
+    let mut encoder = delta_byte_array::Encoder::new();
+    before.iter().for_each(|value| {
+        encoder.write_value(value).unwrap();
+    });
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    assert!(buffer.len() < before.iter().map(Vec::len).sum::<usize>() / 2);
+
+    let mut decoder = delta_byte_array::Decoder::new(&buffer).unwrap();
+    assert_eq!(decoder.read_values().unwrap(), before);
+
+    let mut encoder = delta_byte_array::Encoder::new();
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    let mut decoder = delta_byte_array::Decoder::new(&buffer).unwrap();
+    assert!(decoder.read_values().unwrap().is_empty());
+
+    // A single mini block of 64 values isn't a valid Parquet block, so it is never written.
+    let mut encoder = Encoder::new(1).with_format(Format::Parquet);
+    encoder.write_integer(1).unwrap();
+    assert!(encoder.write(&mut vec![]).is_err());
+    assert!(StreamingEncoder::new(
+        Encoder::new(3).with_format(Format::Parquet),
+        std::io::Cursor::new(vec![])
+    )
+    .is_err());
+}
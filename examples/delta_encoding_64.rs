@@ -14,7 +14,7 @@ fn main() {
     let mut encoder = delta_encoding_64::Encoder::new(1);
 
     before.iter().for_each(|i| {
-        encoder.write_integer(*i as i64).unwrap();
+        encoder.write_integer(*i).unwrap();
     });
 
     encoder.flush().unwrap();
@@ -29,6 +29,26 @@ fn main() {
 
     assert_eq!(before, numbers);
 
+    let timestamps: Vec<i64> = (0..10_000).map(|i| 1_600_000_000_000 + i * 1000).collect();
+    let mut encoder =
+        delta_encoding_64::Encoder::new(1).with_mode(delta_encoding_64::Mode::DeltaOfDelta);
+    timestamps.iter().for_each(|i| {
+        encoder.write_integer(*i).unwrap();
+    });
+    encoder.flush().unwrap();
+    // Evenly spaced timestamps leave nothing to pack.
+    assert_eq!(encoder.bit_widths, vec![0]);
+
+    let mut buffer = std::io::Cursor::new(vec![]);
+    encoder.write(&mut buffer).unwrap();
+    // One min delta and one bit width byte per block of 64 values.
+    assert!(buffer.get_ref().len() < timestamps.len() / 64 * 2 + 32);
+
+    buffer.rewind().unwrap();
+    let mut decoder = delta_encoding_64::Decoder::new(buffer).unwrap();
+    assert_eq!(decoder.mode(), delta_encoding_64::Mode::DeltaOfDelta);
+    assert_eq!(timestamps, decoder.read_integers().unwrap());
+
     let mut delta_vec = delta_encoding_64::DeltaVec::new();
     delta_vec.push(10);
     delta_vec.extend(vec![1, 2, 4]);
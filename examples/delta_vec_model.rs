@@ -0,0 +1,204 @@
+use encoding_rust::delta_encoding_64::{DeltaVec, DeltaVecDecimal};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rust_decimal::Decimal;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+fn hash(value: &DeltaVec) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn random_value(rng: &mut ChaCha8Rng) -> i64 {
+    match rng.gen_range(0..10) {
+        0 => i64::MIN,
+        1 => i64::MAX,
+        2 => rng.gen(),
+        _ => rng.gen_range(-1000..1000),
+    }
+}
+
+fn check(delta_vec: &DeltaVec, model: &[i64]) {
+    assert_eq!(delta_vec.len(), model.len());
+    assert_eq!(delta_vec.is_empty(), model.is_empty());
+    assert_eq!(delta_vec.to_vec(), model);
+    assert_eq!(delta_vec.iter().len(), model.len());
+    assert!(delta_vec.iter().eq(model.iter().copied()));
+    assert_eq!(delta_vec.first(), model.first().copied());
+    assert_eq!(delta_vec.last(), model.last().copied());
+    assert_eq!(delta_vec.clone().into_iter().collect::<Vec<_>>(), model);
+
+    let from_model = DeltaVec::from(model.to_vec());
+    assert_eq!(*delta_vec, from_model);
+    assert_eq!(hash(delta_vec), hash(&from_model));
+}
+
+/// Applies random operations to a `DeltaVec` and a plain `Vec`, checking they agree
+/// after every one.
+fn run_delta_vec(seed: u64) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut delta_vec = DeltaVec::new();
+    let mut model: Vec<i64> = vec![];
+
+    for _ in 0..300 {
+        match rng.gen_range(0..8) {
+            0 | 1 => {
+                let value = random_value(&mut rng);
+                delta_vec.push(value);
+                model.push(value);
+            }
+            2 | 3 => {
+                let items: Vec<i64> = (0..rng.gen_range(0..100))
+                    .map(|_| random_value(&mut rng))
+                    .collect();
+                delta_vec.extend(items.clone());
+                model.extend(items);
+            }
+            4 => {
+                delta_vec.clear();
+                model.clear();
+            }
+            5 => {
+                let items: Vec<i64> = (0..rng.gen_range(0..20))
+                    .map(|_| random_value(&mut rng))
+                    .collect();
+                delta_vec.replace(items.clone());
+                model = items;
+            }
+            6 => {
+                let json = serde_json::to_string(&delta_vec).unwrap();
+                delta_vec = serde_json::from_str(&json).unwrap();
+                let binary = bincode::serialize(&delta_vec).unwrap();
+                delta_vec = bincode::deserialize(&binary).unwrap();
+                assert_eq!(serde_json::to_string(&delta_vec).unwrap(), json);
+            }
+            _ => {
+                let binary = bincode::serialize(&delta_vec).unwrap();
+                delta_vec = bincode::deserialize(&binary).unwrap();
+            }
+        }
+        check(&delta_vec, &model);
+    }
+}
+
+/// Decimals below a billion with up to 4 decimal places, or, while `integers` is set,
+/// integers too large to keep 4 decimal places for. Every vector the model holds can then be stored exactly.
+fn random_decimal(rng: &mut ChaCha8Rng, integers: bool) -> Decimal {
+    if integers {
+        let magnitude = rng.gen_range(1_000_000_000_000_000i64..100_000_000_000_000_000);
+        Decimal::from(if rng.gen() { magnitude } else { -magnitude })
+    } else {
+        Decimal::new(
+            rng.gen_range(-999_999_999..1_000_000_000),
+            rng.gen_range(0..=4),
+        )
+    }
+}
+
+/// Applies random operations to a `DeltaVecDecimal` and a plain `Vec`, checking they
+/// agree after every one.
+fn run_delta_vec_decimal(seed: u64) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut delta_vec = DeltaVecDecimal::new();
+    let mut model: Vec<Decimal> = vec![];
+
+    for _ in 0..200 {
+        let integers = model
+            .iter()
+            .any(|value| value.abs() >= Decimal::from(1_000_000_000))
+            || (model.is_empty() && rng.gen_bool(0.3));
+        match rng.gen_range(0..6) {
+            0 | 1 => {
+                let value = random_decimal(&mut rng, integers);
+                delta_vec.push(value);
+                model.push(value);
+            }
+            2 => {
+                let items: Vec<Decimal> = (0..rng.gen_range(0..20))
+                    .map(|_| random_decimal(&mut rng, integers))
+                    .collect();
+                delta_vec.extend(items.clone());
+                model.extend(items);
+            }
+            3 => {
+                delta_vec.clear();
+                model.clear();
+            }
+            4 => {
+                let integers = rng.gen_bool(0.3);
+                let items: Vec<Decimal> = (0..rng.gen_range(0..20))
+                    .map(|_| random_decimal(&mut rng, integers))
+                    .collect();
+                delta_vec.replace(items.clone());
+                model = items;
+            }
+            _ => {
+                let json = serde_json::to_string(&delta_vec).unwrap();
+                delta_vec = serde_json::from_str(&json).unwrap();
+                let binary = bincode::serialize(&delta_vec).unwrap();
+                delta_vec = bincode::deserialize(&binary).unwrap();
+            }
+        }
+        assert_eq!(delta_vec.len(), model.len());
+        assert_eq!(delta_vec.is_empty(), model.is_empty());
+        assert_eq!(delta_vec.to_vec(), model);
+    }
+}
+
+fn main() {
+    // Fixed cases for the bugs the random operations below check for in general.
+    let mut delta_vec = DeltaVec::from(vec![1, 2, 3]);
+    delta_vec.clear();
+    assert_eq!(delta_vec.len(), 0);
+    assert!(delta_vec.is_empty());
+    assert_eq!(delta_vec, DeltaVec::new());
+
+    // Clearing forgets large values, so they no longer limit the decimal places kept.
+    let mut decimals = DeltaVecDecimal::new();
+    decimals.push(Decimal::from(1_000_000_000_000_000i64));
+    decimals.clear();
+    assert_eq!(decimals.len(), 0);
+    decimals.push(Decimal::new(12_345_678, 8));
+    assert_eq!(decimals.to_vec(), [Decimal::new(12_345_678, 8)]);
+
+    // So does replacing them, and large negative values limit them too.
+    decimals.replace(vec![Decimal::from(-1_000_000_000_000_000i64)]);
+    assert_eq!(
+        decimals.to_vec(),
+        [Decimal::from(-1_000_000_000_000_000i64)]
+    );
+    decimals.replace(vec![Decimal::new(5, 1)]);
+    assert_eq!(decimals.to_vec(), [Decimal::new(5, 1)]);
+
+    // Deserializing checks the multiplier and restores the invariants older versions
+    // didn't keep.
+    let mut json = serde_json::to_value(&decimals).unwrap();
+    for multiplier in ["0", "-10", "0.5", "10.5"] {
+        json["multiplier"] = multiplier.into();
+        assert!(serde_json::from_value::<DeltaVecDecimal>(json.clone()).is_err());
+    }
+    let mut legacy_empty = serde_json::to_value(DeltaVecDecimal::new()).unwrap();
+    legacy_empty["multiplier"] = "1000".into();
+    legacy_empty["max_value"] = Decimal::MIN.to_string().into();
+    let decoded: DeltaVecDecimal = serde_json::from_value(legacy_empty).unwrap();
+    assert_eq!(
+        serde_json::to_value(&decoded).unwrap(),
+        serde_json::to_value(DeltaVecDecimal::new()).unwrap()
+    );
+    json["multiplier"] = "10".into();
+    json["max_value"] = "-7".into();
+    let decoded: DeltaVecDecimal = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded.to_vec(), [Decimal::new(5, 1)]);
+    let max_value = serde_json::to_value(&decoded).unwrap()["max_value"].take();
+    assert_eq!(
+        max_value.as_str().unwrap().parse::<Decimal>().unwrap(),
+        Decimal::new(5, 1)
+    );
+
+    for seed in 0..50 {
+        run_delta_vec(seed);
+        run_delta_vec_decimal(seed);
+    }
+}
@@ -0,0 +1,86 @@
+use encoding_rust::delta_encoding_64::{DeltaVec, Encoder};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+fn hash(value: &DeltaVec) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn main() {
+    let values: Vec<i64> = (0..500).map(|i| (i * 31) % 77 - i).collect();
+    let total: i64 = values.iter().sum();
+
+    let empty = DeltaVec::default();
+    assert!(empty.is_empty());
+    assert_eq!(empty.first(), None);
+    assert_eq!(empty.last(), None);
+    assert_eq!(empty.iter().count(), 0);
+    assert_eq!(empty.clone().into_iter().next(), None);
+    assert_eq!(empty, DeltaVec::from(vec![]));
+
+    let from_vec = DeltaVec::from(values.clone());
+    let collected: DeltaVec = values.iter().copied().collect();
+    assert_eq!(from_vec, collected);
+    assert_eq!(from_vec.len(), values.len());
+    assert_eq!(from_vec.first(), Some(values[0]));
+    assert_eq!(from_vec.last(), values.last().copied());
+
+    let mut iter = from_vec.iter();
+    assert_eq!(iter.len(), values.len());
+    assert_eq!(iter.next(), Some(values[0]));
+    assert_eq!(iter.len(), values.len() - 1);
+    assert_eq!(iter.collect::<Vec<_>>(), &values[1..]);
+    assert!(from_vec.iter().eq(values.iter().copied()));
+    assert_eq!((&from_vec).into_iter().sum::<i64>(), total);
+    let mut sum = 0;
+    for value in &from_vec {
+        sum += value;
+    }
+    assert_eq!(sum, total);
+    assert_eq!(from_vec.clone().into_iter().collect::<Vec<_>>(), values);
+
+    let mut extended = DeltaVec::new();
+    extended.extend(values[..100].iter().copied());
+    Extend::extend(&mut extended, values[100..300].to_vec());
+    extended.extend((300..500).map(|i| values[i]));
+    extended.extend(std::iter::empty());
+    assert_eq!(extended, from_vec);
+    assert_eq!(hash(&extended), hash(&from_vec));
+
+    let mut pushed = DeltaVec::new();
+    for value in &values {
+        pushed.push(*value);
+    }
+    assert_eq!(pushed, from_vec);
+
+    let other = DeltaVec::from(vec![1, 2, 3]);
+    assert_ne!(other, from_vec);
+    assert_ne!(other, DeltaVec::from(vec![1, 2, 4]));
+    assert_ne!(other, DeltaVec::from(vec![1, 2]));
+    assert_ne!(hash(&other), hash(&from_vec));
+
+    // Equality and hashing look at the values, not at how they were encoded.
+    let mut encoder = Encoder::new(1);
+    values
+        .iter()
+        .for_each(|value| encoder.write_integer(*value).unwrap());
+    let json = format!(
+        r#"{{"bytes":{:?},"len":{}}}"#,
+        encoder.finish().unwrap().to_vec(),
+        values.len()
+    );
+    let legacy: DeltaVec = serde_json::from_str(&json).unwrap();
+    assert_ne!(legacy.as_bytes(), from_vec.as_bytes());
+    assert_eq!(legacy, from_vec);
+    assert_eq!(hash(&legacy), hash(&from_vec));
+
+    let set: HashSet<DeltaVec> = [from_vec.clone(), legacy, other.clone(), empty.clone()]
+        .into_iter()
+        .collect();
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(&other));
+    assert!(set.contains(&empty));
+}
@@ -0,0 +1,68 @@
+use encoding_rust::dictionary;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let currencies = ["EUR", "USD", "GBP", "JPY", "CHF"];
+    let before: Vec<&str> = (0..1000)
+        .map(|_| currencies[rng.gen_range(0..currencies.len())])
+        .collect();
+
+    let mut encoder = dictionary::Encoder::<Vec<u8>>::new();
+    before.iter().for_each(|currency| {
+        encoder.write_value(currency.as_bytes()).unwrap();
+    });
+    assert_eq!(encoder.dictionary.len(), currencies.len());
+    assert_eq!(encoder.bit_width(), 3);
+
+    let mut dictionary_page = vec![];
+    encoder.write_dictionary(&mut dictionary_page).unwrap();
+    let mut indices = vec![];
+    encoder.write_indices(&mut indices).unwrap();
+    assert!(indices.len() < before.len() / 2);
+
+    let mut decoder = dictionary::Decoder::<Vec<u8>>::new(&dictionary_page, &indices).unwrap();
+    let values = decoder.read_values(before.len()).unwrap();
+    let values: Vec<&str> = values
+        .iter()
+        .map(|value| std::str::from_utf8(value).unwrap())
+        .collect();
+    assert_eq!(before, values);
+
+    let exchanges: Vec<i64> = (0..1000).map(|i| [7, 42, 1 << 40][i % 3]).collect();
+    let mut encoder = dictionary::Encoder::<i64>::new();
+    exchanges.iter().for_each(|exchange| {
+        encoder.write_value(exchange).unwrap();
+    });
+
+    let mut dictionary_page = vec![];
+    encoder.write_dictionary(&mut dictionary_page).unwrap();
+    let mut indices = vec![];
+    encoder.write_indices(&mut indices).unwrap();
+
+    let mut decoder = dictionary::Decoder::<i64>::new(&dictionary_page, &indices).unwrap();
+    let mut raw_indices = vec![0; 6];
+    assert_eq!(decoder.get_indices(&mut raw_indices).unwrap(), 6);
+    assert_eq!(raw_indices, vec![0, 1, 2, 0, 1, 2]);
+    assert_eq!(decoder.read_values(2000).unwrap(), exchanges[6..].to_vec());
+    assert!(decoder.all_read());
+
+    // The padding of the last bit packed group does not turn into values.
+    let short = [5_i64, 6, 7, 5, 6];
+    let mut encoder = dictionary::Encoder::<i64>::new();
+    short.iter().for_each(|value| {
+        encoder.write_value(value).unwrap();
+    });
+
+    let mut dictionary_page = vec![];
+    encoder.write_dictionary(&mut dictionary_page).unwrap();
+    let mut indices = vec![];
+    encoder.write_indices(&mut indices).unwrap();
+
+    let mut decoder = dictionary::Decoder::<i64>::new(&dictionary_page, &indices).unwrap();
+    assert_eq!(decoder.read_values(100).unwrap(), short.to_vec());
+    assert!(decoder.read_values(100).unwrap().is_empty());
+    let mut decoder = dictionary::Decoder::<i64>::new(&dictionary_page, &indices[..2]).unwrap();
+    assert!(decoder.read_values(100).is_err());
+}
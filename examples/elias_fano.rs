@@ -0,0 +1,59 @@
+use encoding_rust::elias_fano::EliasFano;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let mut row_id = 0;
+    let row_ids: Vec<u64> = (0..100_000)
+        .map(|_| {
+            row_id += rng.gen_range(0..20);
+            row_id
+        })
+        .collect();
+
+    let sequence = EliasFano::new(&row_ids).unwrap();
+    assert_eq!(sequence.len(), row_ids.len());
+    // Average gap of 10 needs about 3 lower bits and 2 upper bits per value.
+    assert!(sequence.size_in_bytes() < row_ids.len() * 6 / 8);
+
+    for _ in 0..1000 {
+        let index = rng.gen_range(0..row_ids.len());
+        assert_eq!(sequence.get(index), Some(row_ids[index]));
+
+        let x = rng.gen_range(0..row_id + 10);
+        let expected = row_ids.partition_point(|value| *value < x);
+        assert_eq!(
+            sequence.next_geq(x),
+            row_ids.get(expected).map(|value| (expected, *value))
+        );
+    }
+    assert_eq!(sequence.get(row_ids.len()), None);
+    assert_eq!(sequence.next_geq(0), Some((0, row_ids[0])));
+    assert_eq!(sequence.iter().collect::<Vec<_>>(), row_ids);
+
+    let mut buffer = vec![];
+    sequence.write(&mut buffer).unwrap();
+    assert_eq!(EliasFano::read(&mut buffer.as_slice()).unwrap(), sequence);
+
+    let small = EliasFano::new(&[3, 3, 7, 1 << 40]).unwrap();
+    assert_eq!(small.next_geq(4), Some((2, 7)));
+    assert_eq!(small.next_geq(8), Some((3, 1 << 40)));
+    assert_eq!(small.next_geq((1 << 40) + 1), None);
+
+    let empty = EliasFano::new(&[]).unwrap();
+    assert_eq!(empty.next_geq(0), None);
+    assert!(EliasFano::new(&[2, 1]).is_err());
+
+    // Corrupted headers are errors rather than panics or huge allocations.
+    assert!(EliasFano::read(&mut [0x7f; 12].as_slice()).is_err());
+    let mut huge = vec![0x7f; 9];
+    huge.push(0x81);
+    huge.push(0xbf);
+    huge.extend_from_slice(&huge.clone()[..10]);
+    assert!(EliasFano::read(&mut huge.as_slice()).is_err());
+    let mut truncated = vec![0x7f, 0x7f, 0x7f, 0x80, 0x81];
+    truncated.extend_from_slice(&[0x7f, 0x7f, 0x7f, 0x80]);
+    truncated.extend_from_slice(&buffer);
+    assert!(EliasFano::read(&mut truncated.as_slice()).is_err());
+}
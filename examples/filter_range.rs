@@ -0,0 +1,87 @@
+use encoding_rust::delta_encoding_64::{Decoder, Encoder, Mode};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn encode(values: &[i64], mode: Mode, block_index: bool) -> Vec<u8> {
+    let mut encoder = Encoder::new(2)
+        .with_mode(mode)
+        .with_block_index(block_index);
+    values.iter().for_each(|i| {
+        encoder.write_integer(*i).unwrap();
+    });
+    encoder.flush().unwrap();
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    buffer
+}
+
+fn matching(values: &[i64], range: impl std::ops::RangeBounds<i64>) -> Vec<usize> {
+    (0..values.len())
+        .filter(|i| range.contains(&values[*i]))
+        .collect()
+}
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(35);
+    let mut ts = 1_600_000_000_000;
+    let timestamps: Vec<i64> = (0..10_000)
+        .map(|_| {
+            ts += rng.gen_range(900..1100);
+            ts
+        })
+        .collect();
+
+    for mode in [Mode::Delta, Mode::DeltaOfDelta] {
+        let indexed = encode(&timestamps, mode, true);
+        let plain = encode(&timestamps, mode, false);
+
+        let (a, b) = (timestamps[2000], timestamps[2100]);
+        for buffer in [&indexed, &plain] {
+            let mut decoder = Decoder::new(buffer.as_slice()).unwrap();
+            assert_eq!(
+                decoder.filter_range(a..=b).unwrap(),
+                (2000..=2100).collect::<Vec<_>>()
+            );
+        }
+
+        let ranges = [
+            (timestamps[0], timestamps[0]),
+            (timestamps[1], timestamps[5000]),
+            (timestamps[9999], i64::MAX),
+            (0, 1),
+        ];
+        for (a, b) in ranges {
+            let mut decoder = Decoder::new(indexed.as_slice()).unwrap();
+            assert_eq!(
+                decoder.filter_range(a..b).unwrap(),
+                matching(&timestamps, a..b)
+            );
+            assert!(decoder.all_read());
+        }
+
+        let mut decoder = Decoder::new(indexed.as_slice()).unwrap();
+        assert_eq!(decoder.filter_range(..).unwrap().len(), timestamps.len());
+
+        let mut decoder = Decoder::new(indexed.as_slice()).unwrap();
+        assert_eq!(decoder.read_integers().unwrap(), timestamps);
+    }
+
+    let values: Vec<i64> = (0..3000).map(|_| rng.gen_range(-50..50)).collect();
+    let buffer = encode(&values, Mode::Delta, true);
+    let mut decoder = Decoder::new(buffer.as_slice()).unwrap();
+    assert_eq!(
+        decoder.filter_range(-5..5).unwrap(),
+        matching(&values, -5..5)
+    );
+
+    for values in [vec![], vec![7], vec![7, 8]] {
+        let buffer = encode(&values, Mode::DeltaOfDelta, true);
+        let mut decoder = Decoder::new(buffer.as_slice()).unwrap();
+        assert_eq!(decoder.filter_range(8..).unwrap(), matching(&values, 8..));
+    }
+
+    let buffer = encode(&timestamps, Mode::Delta, true);
+    let mut decoder = Decoder::new(buffer.as_slice()).unwrap();
+    decoder.read_integer().unwrap();
+    assert!(decoder.filter_range(..).is_err());
+}
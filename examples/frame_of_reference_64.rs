@@ -0,0 +1,64 @@
+use encoding_rust::{delta_encoding_64, frame_of_reference_64};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::io::Seek;
+use vlq::WriteVlqExt;
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let status_codes = [200, 201, 204, 301, 404, 500];
+    let before: Vec<i64> = (0..1000)
+        .map(|_| status_codes[rng.gen_range(0..status_codes.len())])
+        .collect();
+
+    let mut encoder = frame_of_reference_64::Encoder::new(2);
+    before.iter().for_each(|i| {
+        encoder.write_integer(*i).unwrap();
+    });
+    encoder.flush().unwrap();
+
+    let mut buffer = std::io::Cursor::new(vec![]);
+    encoder.write(&mut buffer).unwrap();
+
+    let mut delta_encoder = delta_encoding_64::Encoder::new(2);
+    before.iter().for_each(|i| {
+        delta_encoder.write_integer(*i).unwrap();
+    });
+    delta_encoder.flush().unwrap();
+
+    let mut delta_buffer = std::io::Cursor::new(vec![]);
+    delta_encoder.write(&mut delta_buffer).unwrap();
+
+    assert!(buffer.get_ref().len() < delta_buffer.get_ref().len());
+
+    buffer.rewind().unwrap();
+    let mut decoder = frame_of_reference_64::Decoder::new(buffer.clone()).unwrap();
+    assert_eq!(before, decoder.read_integers().unwrap());
+
+    buffer.rewind().unwrap();
+    assert!(delta_encoding_64::Decoder::new(buffer).is_err());
+
+    // write flushes the pending values itself and leaves the encoder ready for a new stream.
+    let mut unflushed = vec![];
+    for chunk in before.chunks(300) {
+        chunk.iter().for_each(|i| {
+            encoder.write_integer(*i).unwrap();
+        });
+        let mut buffer = std::io::Cursor::new(vec![]);
+        encoder.write(&mut buffer).unwrap();
+        unflushed.push(buffer.into_inner());
+    }
+    for (chunk, bytes) in before.chunks(300).zip(unflushed) {
+        let mut decoder = frame_of_reference_64::Decoder::new(bytes.as_slice()).unwrap();
+        assert_eq!(chunk, decoder.read_integers().unwrap());
+        assert!(decoder.all_read());
+    }
+
+    // A huge count over a short stream fails without allocating for it.
+    let mut truncated = vec![];
+    for value in [64 | 0b10, 1, 1 << 40, 0_u64] {
+        truncated.write_vlq(value).unwrap();
+    }
+    let mut decoder = frame_of_reference_64::Decoder::new(truncated.as_slice()).unwrap();
+    assert!(decoder.read_integers().is_err());
+}
@@ -0,0 +1,70 @@
+use encoding_rust::delta_encoding_64::{NullableDecoder, NullableEncoder};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::io::Seek;
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let before: Vec<Option<i64>> = (0..1000)
+        .map(|i| rng.gen_bool(0.8).then_some(1_600_000_000_000 + i * 1000))
+        .collect();
+
+    let mut encoder = NullableEncoder::new(1);
+    before.iter().for_each(|i| {
+        encoder.write_integer(*i).unwrap();
+    });
+    assert_eq!(
+        encoder.null_count(),
+        before.iter().filter(|i| i.is_none()).count()
+    );
+
+    let mut buffer = std::io::Cursor::new(vec![]);
+    encoder.write(&mut buffer).unwrap();
+
+    buffer.rewind().unwrap();
+    let mut decoder = NullableDecoder::new(buffer.clone()).unwrap();
+    assert_eq!(before, decoder.read_integers().unwrap());
+
+    buffer.rewind().unwrap();
+    let mut decoder = NullableDecoder::new(buffer).unwrap();
+    let mut values = vec![-1; 2000];
+    let mut validity = vec![0xFF; 250];
+    assert!(decoder.read_into(&mut values, &mut validity[..1]).is_err());
+    let mut offset = 0;
+    for batch in [3, 100, 2000] {
+        let count = decoder
+            .read_into(&mut values[..batch], &mut validity)
+            .unwrap();
+        for i in 0..count {
+            let value = before[offset + i];
+            assert_eq!(decoder.is_valid(offset + i), value.is_some());
+            assert_eq!(validity[i / 8] & (1 << (i % 8)) != 0, value.is_some());
+            assert_eq!(values[i], value.unwrap_or_default());
+        }
+        offset += count;
+    }
+    assert_eq!(offset, before.len());
+    assert!(decoder.all_read());
+
+    let mut encoder = NullableEncoder::new(1);
+    encoder.write_integer(None).unwrap();
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    let mut decoder = NullableDecoder::new(buffer.as_slice()).unwrap();
+    assert_eq!(decoder.read_integers().unwrap(), vec![None]);
+
+    encoder.write_integer(Some(4)).unwrap();
+    encoder.write_integer(None).unwrap();
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    assert_eq!(encoder.total_count, 0);
+    let mut decoder = NullableDecoder::new(buffer.as_slice()).unwrap();
+    assert_eq!(decoder.read_integers().unwrap(), vec![Some(4), None]);
+
+    // Corrupted counts are errors rather than panics or huge allocations.
+    let mut corrupted = vec![0x7f; 12];
+    corrupted.extend_from_slice(&buffer);
+    assert!(NullableDecoder::new(corrupted.as_slice()).is_err());
+    let huge = [0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x80, 0xff];
+    assert!(NullableDecoder::new(&huge[..]).is_err());
+}
@@ -0,0 +1,79 @@
+use encoding_rust::parquet_page::{
+    PageHeader, PageReader, PageWriter, ENCODING_DELTA_BINARY_PACKED, PAGE_TYPE_DATA_PAGE,
+};
+
+// Column chunks written by parquet-rs 54.3.1 with data page version 1, no compression or
+// dictionary and pages of 1000 rows:
+// `message m { optional int64 ts; required int64 v; }`
+const OPTIONAL_INT64: &[u8] = include_bytes!("data/optional_int64.pages");
+const REQUIRED_INT64: &[u8] = include_bytes!("data/required_int64.pages");
+
+fn optional_values() -> Vec<Option<i64>> {
+    (0..2500)
+        .map(|i| (i % 7 != 3).then_some(1_600_000_000_000 + i * 1000 + (i * i) % 17))
+        .collect()
+}
+
+fn required_values() -> Vec<Option<i64>> {
+    (0..2500).map(|i| Some(i * i - 5000 * i)).collect()
+}
+
+fn main() {
+    let mut reader = PageReader::new(OPTIONAL_INT64, true);
+    assert_eq!(reader.read_page().unwrap().unwrap().len(), 1000);
+    assert_eq!(reader.read_pages().unwrap(), optional_values()[1000..]);
+
+    let mut reader = PageReader::new(OPTIONAL_INT64, true);
+    assert_eq!(reader.read_pages().unwrap(), optional_values());
+    let mut reader = PageReader::new(REQUIRED_INT64, false);
+    assert_eq!(reader.read_pages().unwrap(), required_values());
+
+    let header = PageHeader::read(&mut &REQUIRED_INT64[..]).unwrap();
+    assert_eq!(header.page_type, PAGE_TYPE_DATA_PAGE);
+    let data_page_header = header.data_page_header.unwrap();
+    assert_eq!(data_page_header.num_values, 1000);
+    assert_eq!(data_page_header.encoding, ENCODING_DELTA_BINARY_PACKED);
+
+    for (values, nullable) in [(optional_values(), true), (required_values(), false)] {
+        let mut chunk = vec![];
+        let mut writer = PageWriter::new(nullable);
+        for page in values.chunks(1000) {
+            for value in page {
+                writer.write_value(*value).unwrap();
+            }
+            writer.write_page(&mut chunk).unwrap();
+        }
+
+        let mut reader = PageReader::new(chunk.as_slice(), nullable);
+        assert_eq!(reader.read_pages().unwrap(), values);
+
+        let header = PageHeader::read(&mut chunk.as_slice()).unwrap();
+        assert_eq!(header.data_page_header.unwrap().num_values, 1000);
+    }
+
+    let mut writer = PageWriter::new(false);
+    assert!(writer.write_value(None).is_err());
+    let mut chunk = vec![];
+    writer.write_page(&mut chunk).unwrap();
+    let mut reader = PageReader::new(chunk.as_slice(), false);
+    assert_eq!(reader.read_page().unwrap(), Some(vec![]));
+    assert_eq!(reader.read_page().unwrap(), None);
+
+    let truncated = &OPTIONAL_INT64[..OPTIONAL_INT64.len() - 1];
+    let mut reader = PageReader::new(truncated, true);
+    assert!(reader.read_pages().is_err());
+
+    // A corrupted value count is an error rather than a huge allocation.
+    let mut rest = OPTIONAL_INT64;
+    let mut header = PageHeader::read(&mut rest).unwrap();
+    header.data_page_header.as_mut().unwrap().num_values = i32::MAX;
+    let mut page = vec![];
+    header.write(&mut page).unwrap();
+    page.extend_from_slice(&rest[..header.compressed_page_size as usize]);
+    let mut reader = PageReader::new(page.as_slice(), true);
+    assert!(reader.read_page().is_err());
+
+    // Field id deltas adding up past `i16::MAX` are an error rather than an overflow.
+    let fields = [0xf5, 0x00].repeat(i16::MAX as usize / 15 + 1);
+    assert!(PageHeader::read(&mut fields.as_slice()).is_err());
+}
@@ -0,0 +1,82 @@
+use bytes::BytesMut;
+use encoding_rust::delta_encoding_64::{Encoder, Mode, SliceDecoder};
+
+fn fresh(values: &[i64]) -> Vec<u8> {
+    let mut encoder = Encoder::new(2).with_mode(Mode::DeltaOfDelta);
+    values.iter().for_each(|i| {
+        encoder.write_integer(*i).unwrap();
+    });
+    encoder.flush().unwrap();
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    buffer
+}
+
+fn main() {
+    let pages: Vec<Vec<i64>> = (0..100)
+        .map(|page| (0..page * 37).map(|i| page * 1000 + i * i).collect())
+        .collect();
+
+    let mut encoder = Encoder::new(2).with_mode(Mode::DeltaOfDelta);
+    let mut capacity = 0;
+    for page in &pages {
+        page.iter().for_each(|i| {
+            encoder.write_integer(*i).unwrap();
+        });
+        let bytes = encoder.finish().unwrap();
+        assert_eq!(bytes.as_ref(), fresh(page).as_slice());
+        assert_eq!(encoder.total_count, 0);
+        assert_eq!(encoder.mode(), Mode::DeltaOfDelta);
+
+        let mut decoder = SliceDecoder::new(&bytes).unwrap();
+        assert_eq!(&decoder.read_integers().unwrap(), page);
+
+        assert!(encoder.blocks_buffer.get_ref().capacity() >= capacity);
+        capacity = encoder.blocks_buffer.get_ref().capacity();
+    }
+    assert!(capacity > 0);
+
+    let mut buf = BytesMut::new();
+    let mut vec = vec![];
+    for page in &pages[..3] {
+        for output in [0, 1] {
+            page.iter().for_each(|i| {
+                encoder.write_integer(*i).unwrap();
+            });
+            if output == 0 {
+                encoder.write_to_buf(&mut buf).unwrap();
+            } else {
+                encoder.write(&mut vec).unwrap();
+            }
+            assert_eq!(encoder.total_count, 0);
+        }
+    }
+    assert_eq!(buf.as_ref(), vec.as_slice());
+    let expected: Vec<u8> = pages[..3].iter().flat_map(|page| fresh(page)).collect();
+    assert_eq!(vec, expected);
+
+    encoder.write_integer(1).unwrap();
+    encoder.reset();
+    encoder.write_integer(5).unwrap();
+    encoder.write_integer(7).unwrap();
+    let bytes = encoder.finish().unwrap();
+    assert_eq!(bytes.as_ref(), fresh(&[5, 7]).as_slice());
+
+    let mut encoder = Encoder::new(1).with_mode(Mode::DeltaOfDelta);
+    let mut parquet = Encoder::new(1)
+        .with_mode(Mode::DeltaOfDelta)
+        .with_format(encoding_rust::delta_encoding_64::Format::Parquet);
+    for i in 0..100 {
+        encoder.write_integer(i).unwrap();
+        parquet.write_integer(i).unwrap();
+    }
+    assert!(parquet.write(&mut vec![]).is_err());
+    assert_eq!(parquet.total_count, 0);
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    let mut decoder = SliceDecoder::new(&buffer).unwrap();
+    assert_eq!(
+        decoder.read_integers().unwrap(),
+        (0..100).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,61 @@
+use encoding_rust::rle;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn encode(values: &[u32], bit_width: u8) -> Vec<u8> {
+    let mut encoder = rle::Encoder::new(bit_width);
+    values.iter().for_each(|i| {
+        encoder.write_value(*i).unwrap();
+    });
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    buffer
+}
+
+fn decode(bytes: &[u8], bit_width: u8, len: usize) -> Vec<u32> {
+    let mut decoder = rle::Decoder::new(bytes, bit_width);
+    let mut values = vec![0; len];
+    let mut read = 0;
+    while read < len {
+        // Odd batch sizes cross run and mini block boundaries.
+        let end = (read + 37).min(len);
+        read += decoder.get_batch(&mut values[read..end]).unwrap();
+    }
+    values
+}
+
+fn main() {
+    // Examples from the Parquet specification.
+    assert_eq!(
+        encode(&[0, 1, 2, 3, 4, 5, 6, 7], 3),
+        vec![0x03, 0x88, 0xC6, 0xFA]
+    );
+    assert_eq!(encode(&[4; 10], 3), vec![0x14, 0x04]);
+
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let mut levels = vec![];
+    while levels.len() < 10_000 {
+        let level = rng.gen_range(0..4);
+        let run = if rng.gen_bool(0.5) {
+            rng.gen_range(1..100)
+        } else {
+            1
+        };
+        levels.extend(std::iter::repeat_n(level, run));
+    }
+
+    for bit_width in [2, 7, 13, 32] {
+        let bytes = encode(&levels, bit_width);
+        assert_eq!(levels, decode(&bytes, bit_width, levels.len()));
+    }
+
+    let bytes = encode(&levels, 2);
+    assert!(bytes.len() < levels.len() / 4);
+
+    let bytes = encode(&[0; 1000], 0);
+    assert_eq!(vec![0; 1000], decode(&bytes, 0, 1000));
+
+    let mut decoder = rle::Decoder::new(&bytes, 0);
+    let mut values = vec![0; 2000];
+    assert_eq!(decoder.get_batch(&mut values).unwrap(), 1000);
+}
@@ -0,0 +1,134 @@
+use encoding_rust::delta_encoding_64::{serde_delta, DeltaVec, DeltaVecDecimal};
+use rust_decimal::Decimal;
+use serde_derive::{Deserialize, Serialize};
+
+/// What `DeltaVec` serialized to before it stored its bytes compactly.
+#[derive(Serialize)]
+struct LegacyDeltaVec {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+/// What `DeltaVecDecimal` serialized to before, with `rust_decimal`'s own string form.
+#[derive(Serialize)]
+struct LegacyDeltaVecDecimal {
+    multiplier: Decimal,
+    max_value: Decimal,
+    delta_vec: LegacyDeltaVec,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Series {
+    name: String,
+    #[serde(with = "serde_delta")]
+    timestamps: Vec<i64>,
+    #[serde(with = "serde_delta")]
+    values: Vec<i64>,
+}
+
+fn main() {
+    let values: Vec<i64> = (0..1000).map(|i| 1_600_000_000 + i * 60).collect();
+    let mut delta_vec = DeltaVec::new();
+    delta_vec.extend(values.clone());
+
+    let json = serde_json::to_string(&delta_vec).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert!(parsed["bytes"].is_string());
+    assert_eq!(parsed["len"], 1000);
+    let decoded: DeltaVec = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.to_vec(), values);
+
+    let binary = bincode::serialize(&delta_vec).unwrap();
+    // The length prefixes of the bytes and `len`, and nothing else.
+    assert_eq!(binary.len(), 8 + delta_vec.as_bytes().len() + 8);
+    let decoded: DeltaVec = bincode::deserialize(&binary).unwrap();
+    assert_eq!(decoded.to_vec(), values);
+
+    // Values persisted in the old representation still load.
+    let legacy = LegacyDeltaVec {
+        bytes: delta_vec.as_bytes().to_vec(),
+        len: delta_vec.len(),
+    };
+    let legacy_json = serde_json::to_string(&legacy).unwrap();
+    assert!(legacy_json.len() > json.len() * 2);
+    let decoded: DeltaVec = serde_json::from_str(&legacy_json).unwrap();
+    assert_eq!(decoded.to_vec(), values);
+    let decoded: DeltaVec = bincode::deserialize(&bincode::serialize(&legacy).unwrap()).unwrap();
+    assert_eq!(decoded.to_vec(), values);
+
+    let empty = DeltaVec::new();
+    let json = serde_json::to_string(&empty).unwrap();
+    assert_eq!(json, r#"{"bytes":"","len":0}"#);
+    let decoded: DeltaVec = serde_json::from_str(&json).unwrap();
+    assert!(decoded.is_empty());
+    assert!(serde_json::from_str::<DeltaVec>(r#"{"bytes":"not base64!","len":0}"#).is_err());
+
+    let mut decimals = DeltaVecDecimal::new();
+    let items: Vec<Decimal> = ["1.5", "2.25", "-3", "100.125"]
+        .iter()
+        .map(|item| item.parse().unwrap())
+        .collect();
+    decimals.extend(items.clone());
+    let json = serde_json::to_string(&decimals).unwrap();
+    let decoded: DeltaVecDecimal = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.to_vec(), items);
+    let decoded: DeltaVecDecimal =
+        bincode::deserialize(&bincode::serialize(&decimals).unwrap()).unwrap();
+    assert_eq!(decoded.to_vec(), items);
+
+    // Decimals keep their string form, so payloads written before still load.
+    let legacy = LegacyDeltaVecDecimal {
+        multiplier: Decimal::from(1000),
+        max_value: "100.125".parse().unwrap(),
+        delta_vec: LegacyDeltaVec {
+            bytes: decimals_bytes(&items),
+            len: items.len(),
+        },
+    };
+    let legacy_binary = bincode::serialize(&legacy).unwrap();
+    assert_eq!(bincode::serialize(&decimals).unwrap(), legacy_binary);
+    let decoded: DeltaVecDecimal = bincode::deserialize(&legacy_binary).unwrap();
+    assert_eq!(decoded.to_vec(), items);
+    let decoded: DeltaVecDecimal =
+        serde_json::from_str(&serde_json::to_string(&legacy).unwrap()).unwrap();
+    assert_eq!(decoded.to_vec(), items);
+
+    let series = Series {
+        name: "cpu".to_string(),
+        timestamps: values.clone(),
+        values: (0..1000).map(|i| (i * 37) % 101 - 50).collect(),
+    };
+    let json = serde_json::to_string(&series).unwrap();
+    let plain_len = serde_json::to_string(&series.timestamps).unwrap().len();
+    assert!(json.len() < plain_len);
+    assert_eq!(serde_json::from_str::<Series>(&json).unwrap(), series);
+    let binary = bincode::serialize(&series).unwrap();
+    assert!(binary.len() < series.timestamps.len() * 8);
+    assert_eq!(bincode::deserialize::<Series>(&binary).unwrap(), series);
+
+    let empty = Series {
+        name: String::new(),
+        timestamps: vec![],
+        values: vec![i64::MIN, i64::MAX],
+    };
+    let json = serde_json::to_string(&empty).unwrap();
+    assert_eq!(serde_json::from_str::<Series>(&json).unwrap(), empty);
+
+    // Streams that don't decode, or have bytes left over, are rejected.
+    let mut parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    parsed["values"] = "gERMVAE=".into();
+    assert!(serde_json::from_value::<Series>(parsed.clone()).is_err());
+    let mut binary = bincode::serialize(&series).unwrap();
+    let last = binary.len() - 1;
+    binary[last] ^= 0xff;
+    assert!(bincode::deserialize::<Series>(&binary).is_err());
+}
+
+/// The bytes `DeltaVecDecimal` stores for `items` scaled by 1000.
+fn decimals_bytes(items: &[Decimal]) -> Vec<u8> {
+    let ints: DeltaVec = items
+        .iter()
+        .map(|item| (item * Decimal::from(1000)).try_into().unwrap())
+        .collect();
+    ints.as_bytes().to_vec()
+}
@@ -0,0 +1,105 @@
+use encoding_rust::delta_encoding_64::{Decoder, Encoder, Mode, Stats, StreamingEncoder};
+use std::io::Cursor;
+
+fn encode(values: &[i64], mode: Mode) -> (Encoder, Vec<u8>) {
+    let mut encoder = Encoder::new(1).with_mode(mode).with_stats(true);
+    values.iter().for_each(|i| {
+        encoder.write_integer(*i).unwrap();
+    });
+    encoder.flush().unwrap();
+    let mut buffer = vec![];
+    encoder.clone().write(&mut buffer).unwrap();
+    (encoder, buffer)
+}
+
+fn main() {
+    let values: Vec<i64> = (0..200)
+        .map(|i| if i == 150 { -5 } else { i * 10 })
+        .collect();
+
+    let (encoder, buffer) = encode(&values, Mode::Delta);
+    let stream = Stats {
+        min: -5,
+        max: 1990,
+        count: 200,
+        non_decreasing: false,
+    };
+    assert_eq!(encoder.stats(), Some(stream));
+    assert_eq!(
+        encoder.block_stats(),
+        &[
+            Stats {
+                min: 0,
+                max: 640,
+                count: 65,
+                non_decreasing: true,
+            },
+            Stats {
+                min: 650,
+                max: 1280,
+                count: 64,
+                non_decreasing: true,
+            },
+            Stats {
+                min: -5,
+                max: 1920,
+                count: 64,
+                non_decreasing: false,
+            },
+            Stats {
+                min: 1930,
+                max: 1990,
+                count: 7,
+                non_decreasing: true,
+            },
+        ]
+    );
+
+    let mut decoder = Decoder::new(buffer.as_slice()).unwrap();
+    assert_eq!(decoder.stats(), encoder.stats());
+    assert_eq!(decoder.block_stats(), encoder.block_stats());
+    assert_eq!(decoder.read_integers().unwrap(), values);
+
+    let (encoder, buffer) = encode(&values[..100], Mode::DeltaOfDelta);
+    let decoder = Decoder::new(buffer.as_slice()).unwrap();
+    assert!(decoder.stats().unwrap().non_decreasing);
+    assert_eq!(decoder.block_stats(), encoder.block_stats());
+    assert_eq!(decoder.block_stats()[0].count, 66);
+
+    let (encoder, buffer) = encode(&[3], Mode::DeltaOfDelta);
+    assert_eq!(encoder.stats(), Some(Stats::new(3)));
+    let decoder = Decoder::new(buffer.as_slice()).unwrap();
+    assert_eq!(decoder.stats(), Some(Stats::new(3)));
+    assert!(decoder.block_stats().is_empty());
+
+    let (_, buffer) = encode(&[], Mode::Delta);
+    let decoder = Decoder::new(buffer.as_slice()).unwrap();
+    assert_eq!(decoder.stats(), None);
+
+    let mut plain = vec![];
+    let mut encoder = Encoder::new(1);
+    encoder.write_integer(1).unwrap();
+    encoder.write_integer(2).unwrap();
+    encoder.flush().unwrap();
+    assert_eq!(encoder.stats().unwrap().count, 2);
+    assert!(encoder.block_stats().is_empty());
+    encoder.write(&mut plain).unwrap();
+    assert_eq!(encoder.stats(), None);
+    assert!(encoder.block_stats().is_empty());
+    assert_eq!(Decoder::new(plain.as_slice()).unwrap().stats(), None);
+
+    // Streams only keep the stats of the block being written, even with a block index.
+    let mut streaming =
+        StreamingEncoder::new(Encoder::new(1).with_block_index(true), Cursor::new(vec![])).unwrap();
+    for i in 0..1000 {
+        streaming.write_integer(i).unwrap();
+    }
+    assert!(streaming.encoder.block_stats().is_empty());
+    assert_eq!(streaming.encoder.stats().unwrap().max, 999);
+    let bytes = streaming.finish().unwrap().into_inner();
+    let mut decoder = Decoder::new(bytes.as_slice()).unwrap();
+    assert_eq!(
+        decoder.filter_range(10..20).unwrap(),
+        (10..20).collect::<Vec<_>>()
+    );
+}
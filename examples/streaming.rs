@@ -0,0 +1,62 @@
+use encoding_rust::delta_encoding_64::{Decoder, Encoder, Format, Mode, StreamingEncoder};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::io::{Cursor, Seek, SeekFrom, Write};
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(40);
+    let mut values: Vec<i64> = (0..20_000).map(|_| rng.gen()).collect();
+    values[0] = i64::MIN;
+    values[1] = i64::MAX;
+
+    let encoders = [
+        Encoder::new(1),
+        Encoder::new(2).with_mode(Mode::DeltaOfDelta),
+        Encoder::new(4).with_block_index(true),
+        Encoder::new(2).with_format(Format::Parquet),
+    ];
+    for encoder in encoders {
+        let format = encoder.format();
+        for len in [0, 1, 2, 3, 129, values.len()] {
+            let mut io = Cursor::new(b"prefix".to_vec());
+            io.seek(SeekFrom::End(0)).unwrap();
+
+            let mut streaming = StreamingEncoder::new(encoder.clone(), io).unwrap();
+            for value in &values[..len] {
+                streaming.write_integer(*value).unwrap();
+                assert!(streaming.encoder.blocks_buffer.get_ref().is_empty());
+            }
+            let mut io = streaming.finish().unwrap();
+            io.write_all(b"suffix").unwrap();
+
+            let bytes = io.into_inner();
+            assert_eq!(&bytes[..6], b"prefix");
+            assert_eq!(&bytes[bytes.len() - 6..], b"suffix");
+            let mut decoder = Decoder::with_format(&bytes[6..], format).unwrap();
+            assert_eq!(decoder.read_integers().unwrap(), &values[..len]);
+            assert_eq!(decoder.io.into_inner(), b"suffix");
+        }
+    }
+
+    let path = std::env::temp_dir().join("encoding_rust_streaming_example");
+    let file = std::fs::File::create(&path).unwrap();
+    let mut streaming = StreamingEncoder::new(Encoder::new(1), file).unwrap();
+    for i in 0..100_000 {
+        streaming.write_integer(i * 3).unwrap();
+    }
+    streaming.finish().unwrap();
+    let file = std::fs::File::open(&path).unwrap();
+    let mut decoder = Decoder::new(std::io::BufReader::new(file)).unwrap();
+    assert_eq!(decoder.total_count, 100_000);
+    assert_eq!(
+        decoder.read_integers().unwrap(),
+        (0..100_000).map(|i| i * 3).collect::<Vec<_>>()
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    let io = Cursor::new(vec![]);
+    assert!(StreamingEncoder::new(Encoder::new(1).with_stats(true), io.clone()).is_err());
+    let mut encoder = Encoder::new(1);
+    encoder.write_integer(1).unwrap();
+    assert!(StreamingEncoder::new(encoder, io).is_err());
+}
@@ -0,0 +1,114 @@
+use encoding_rust::delta_encoding_64::{Decoder, DeltaVec, DeltaVecDecimal, Encoder, SliceDecoder};
+use rust_decimal::Decimal;
+use serde_derive::Serialize;
+use vlq::WriteVlqExt;
+
+/// A `DeltaVec` with whatever bytes and length we like.
+#[derive(Serialize)]
+struct RawDeltaVec {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+fn from_json(bytes: Vec<u8>, len: usize) -> Result<DeltaVec, serde_json::Error> {
+    serde_json::from_str(&serde_json::to_string(&RawDeltaVec { bytes, len }).unwrap())
+}
+
+/// A versioned header without flags declaring the given sizes.
+fn header(block_size: u64, mini_blocks: u64, total_count: u64) -> Vec<u8> {
+    let mut header = vec![0x80, b'D', b'L', b'T', 1];
+    for value in [0, block_size, mini_blocks, total_count, 0] {
+        header.write_vlq(value).unwrap();
+    }
+    header
+}
+
+fn main() {
+    let values: Vec<i64> = (0..300).map(|i| (i * 7919) % 1000 - 500).collect();
+    let mut delta_vec = DeltaVec::new();
+    delta_vec.extend(values.clone());
+    let bytes = delta_vec.as_bytes().to_vec();
+
+    assert_eq!(from_json(bytes.clone(), 300).unwrap().to_vec(), values);
+    assert!(from_json(vec![], 0).unwrap().is_empty());
+    // Cleared vectors used to keep their length.
+    let legacy = r#"{"bytes":"","len":3}"#;
+    assert!(serde_json::from_str::<DeltaVec>(legacy).unwrap().is_empty());
+    let legacy = r#"{"delta_vec":{"bytes":"","len":3},"multiplier":"100","max_value":"2.5"}"#;
+    let legacy = serde_json::from_str::<DeltaVecDecimal>(legacy).unwrap();
+    assert!(legacy.is_empty());
+
+    let error = from_json(bytes.clone(), 301).unwrap_err();
+    assert!(error.to_string().contains("length 301 holds 300 values"));
+    assert!(from_json(bytes.clone(), 299).is_err());
+    assert!(from_json(bytes.clone(), 0).is_err());
+    assert!(from_json(bytes[..bytes.len() - 1].to_vec(), 300).is_err());
+    assert!(from_json([&bytes[..], &[0]].concat(), 300).is_err());
+    assert!(from_json(vec![0xff; 16], 300).is_err());
+
+    // A huge total count in a short stream fails without allocating for it.
+    let mut encoder = Encoder::new(1);
+    encoder.write_integer(1).unwrap();
+    let mut huge = encoder.finish().unwrap().to_vec();
+    huge.splice(2..3, [0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x8f]);
+    assert!(from_json(huge, 0x7f_ffff_ffff_ffff).is_err());
+
+    // Neither huge blocks nor more blocks than the bytes can hold are allocated for.
+    let hostile = header(64 << 40, 1 << 40, 1 << 50);
+    assert!(SliceDecoder::new(&hostile).is_err());
+    assert!(Decoder::new(hostile.as_slice()).is_err());
+    assert!(from_json(hostile, 1 << 50).is_err());
+    let hostile = header(64 << 10, 1 << 10, 1 << 50);
+    let error = SliceDecoder::new(&hostile).unwrap_err();
+    assert!(error.to_string().contains("bytes left"));
+    assert!(Decoder::new(hostile.as_slice()).is_err());
+    assert!(from_json(hostile, 1 << 50).is_err());
+    let mut valid = header(64, 1, 2);
+    valid.extend([0x80, 0]);
+    assert_eq!(
+        SliceDecoder::new(&valid).unwrap().read_integers().unwrap(),
+        [0, 0]
+    );
+    // Readers can't tell the input is short, so values are only allocated as they're read.
+    let mut truncated = header(64, 1, 1 << 40);
+    truncated.extend([0x80, 0]);
+    assert!(Decoder::new(truncated.as_slice())
+        .unwrap()
+        .read_integers()
+        .is_err());
+
+    // Streams without checksums, as written before they existed, are still accepted.
+    let mut encoder = Encoder::new(1);
+    values
+        .iter()
+        .for_each(|value| encoder.write_integer(*value).unwrap());
+    let unchecked = encoder.finish().unwrap().to_vec();
+    assert_eq!(from_json(unchecked.clone(), 300).unwrap().to_vec(), values);
+
+    let raw = RawDeltaVec {
+        bytes: bytes.clone(),
+        len: 301,
+    };
+    assert!(bincode::deserialize::<DeltaVec>(&bincode::serialize(&raw).unwrap()).is_err());
+
+    let mut decimals = DeltaVecDecimal::new();
+    decimals.extend(vec![Decimal::new(15, 1), Decimal::new(-25, 2)]);
+    let mut json: serde_json::Value = serde_json::to_value(&decimals).unwrap();
+    json["delta_vec"]["len"] = 3.into();
+    assert!(serde_json::from_value::<DeltaVecDecimal>(json).is_err());
+
+    // Whatever bit is flipped, deserializing either fails or yields a value whose length
+    // is right and that decodes without panicking.
+    for bytes in [bytes, unchecked] {
+        let binary = bincode::serialize(&RawDeltaVec { bytes, len: 300 }).unwrap();
+        for position in 0..binary.len() {
+            for bit in 0..8 {
+                let mut corrupted = binary.clone();
+                corrupted[position] ^= 1 << bit;
+                if let Ok(delta_vec) = bincode::deserialize::<DeltaVec>(&corrupted) {
+                    assert_eq!(delta_vec.to_vec().len(), delta_vec.len());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use encoding_rust::delta_encoding_64::{
+    DeltaVec, Encoder, Format, Mode, SliceDecoder, StreamingEncoder,
+};
+use std::io::Cursor;
+
+const VALUES: [i64; 5] = [5, 7, 6, 100, -3];
+
+fn main() {
+    // Streams written before versioned headers existed.
+    let legacy = [
+        &[192, 129, 133, 138, 77, 129, 8, 105, 102, 197][..],
+        &[0; 61],
+    ]
+    .concat();
+    let mut decoder = SliceDecoder::new(&legacy).unwrap();
+    assert_eq!(decoder.version(), None);
+    assert_eq!(decoder.read_integers().unwrap(), VALUES);
+
+    let legacy = [
+        &[
+            197, 129, 133, 138, 132, 200, 133, 72, 129, 133, 77, 129, 9, 131, 9, 194, 72, 2,
+        ][..],
+        &[0; 69],
+    ]
+    .concat();
+    let mut decoder = SliceDecoder::new(&legacy).unwrap();
+    assert_eq!(decoder.version(), None);
+    assert_eq!(decoder.mode(), Mode::DeltaOfDelta);
+    assert_eq!(decoder.read_integers().unwrap(), VALUES);
+
+    let json = format!(r#"{{"bytes":{:?},"len":5}}"#, legacy);
+    let delta_vec: DeltaVec = serde_json::from_str(&json).unwrap();
+    assert_eq!(delta_vec.to_vec(), VALUES);
+
+    let values: Vec<i64> = (0..1000).map(|i| i * i % 997).collect();
+    let encoders = [
+        Encoder::new(1),
+        Encoder::new(2).with_mode(Mode::DeltaOfDelta),
+        Encoder::new(1).with_block_index(true).with_stats(true),
+        Encoder::new(4)
+            .with_mode(Mode::DeltaOfDelta)
+            .with_block_index(true)
+            .with_checksums(true),
+    ];
+    for encoder in encoders {
+        for len in [0, 1, 2, 3, values.len()] {
+            let mut unversioned = encoder.clone();
+            let mut versioned = encoder.clone().with_versioned_header(true);
+            for value in &values[..len] {
+                unversioned.write_integer(*value).unwrap();
+                versioned.write_integer(*value).unwrap();
+            }
+            let unversioned = unversioned.finish().unwrap();
+            let bytes = versioned.finish().unwrap();
+            assert_eq!(&bytes[..5], &[0x80, b'D', b'L', b'T', 1]);
+            assert!(bytes.len() > unversioned.len());
+
+            let mut decoder = SliceDecoder::new(&bytes).unwrap();
+            assert_eq!(decoder.version(), Some(1));
+            assert_eq!(decoder.mode(), encoder.mode());
+            assert_eq!(decoder.read_integers().unwrap(), &values[..len]);
+            let mut decoder = SliceDecoder::new(&unversioned).unwrap();
+            assert_eq!(decoder.version(), None);
+            assert_eq!(decoder.read_integers().unwrap(), &values[..len]);
+        }
+    }
+
+    let mut encoder = Encoder::new(1).with_versioned_header(true);
+    VALUES
+        .iter()
+        .for_each(|value| encoder.write_integer(*value).unwrap());
+    let bytes = encoder.finish().unwrap().to_vec();
+
+    let mut newer = bytes.clone();
+    newer[4] = 2;
+    let error = SliceDecoder::new(&newer).unwrap_err();
+    assert_eq!(error.to_string(), "Unsupported header version 2");
+
+    let mut corrupted = bytes.clone();
+    corrupted[2] = b'X';
+    assert!(SliceDecoder::new(&corrupted).is_err());
+
+    let mut unknown_flags = bytes.clone();
+    unknown_flags[5] = 0x80 | 0b100000;
+    assert!(SliceDecoder::new(&unknown_flags).is_err());
+
+    for len in 0..6 {
+        assert!(SliceDecoder::new(&bytes[..len]).is_err());
+    }
+
+    let mut streaming = StreamingEncoder::new(
+        Encoder::new(1).with_versioned_header(true),
+        Cursor::new(vec![]),
+    )
+    .unwrap();
+    for value in &values {
+        streaming.write_integer(*value).unwrap();
+    }
+    let bytes = streaming.finish().unwrap().into_inner();
+    let mut decoder = SliceDecoder::new(&bytes).unwrap();
+    assert_eq!(decoder.version(), Some(1));
+    assert_eq!(decoder.read_integers().unwrap(), values);
+
+    let mut encoder = Encoder::new(2)
+        .with_format(Format::Parquet)
+        .with_versioned_header(true);
+    encoder.write_integer(1).unwrap();
+    assert!(encoder.finish().is_err());
+
+    let mut delta_vec = DeltaVec::new();
+    delta_vec.extend(values.clone());
+    assert_eq!(delta_vec.to_vec(), values);
+    assert_eq!(delta_vec.as_bytes()[0], 0x80);
+}
@@ -0,0 +1,58 @@
+use bytes::Bytes;
+use encoding_rust::delta_encoding_64::{
+    BytesDecoder, Decoder, Encoder, Format, Mode, SliceDecoder,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(37);
+    let values: Vec<i64> = (0..5000)
+        .map(|_| rng.gen_range(-1_000_000..1_000_000))
+        .collect();
+
+    let encoders = [
+        Encoder::new(1),
+        Encoder::new(4).with_mode(Mode::DeltaOfDelta),
+        Encoder::new(2).with_block_index(true).with_stats(true),
+        Encoder::new(2).with_format(Format::Parquet),
+    ];
+    for mut encoder in encoders {
+        let format = encoder.format();
+        values.iter().for_each(|i| {
+            encoder.write_integer(*i).unwrap();
+        });
+        encoder.flush().unwrap();
+        let mut buffer = vec![];
+        encoder.write(&mut buffer).unwrap();
+        buffer.extend_from_slice(b"trailer");
+
+        let mut decoder = Decoder::with_format(buffer.as_slice(), format).unwrap();
+        assert_eq!(decoder.read_integers().unwrap(), values);
+
+        let mut decoder = SliceDecoder::with_format(&buffer, format).unwrap();
+        assert_eq!(decoder.read_integers().unwrap(), values);
+        assert_eq!(decoder.io, b"trailer");
+
+        let bytes = Bytes::from(buffer);
+        let mut decoder = BytesDecoder::with_format(bytes.clone(), format).unwrap();
+        assert_eq!(decoder.read_integers().unwrap(), values);
+        assert_eq!(decoder.io.remaining(), Bytes::from_static(b"trailer"));
+    }
+
+    let mut encoder = Encoder::new(1).with_block_index(true);
+    values.iter().for_each(|i| {
+        encoder.write_integer(*i).unwrap();
+    });
+    encoder.flush().unwrap();
+    let mut buffer = vec![];
+    encoder.write(&mut buffer).unwrap();
+    let mut decoder = SliceDecoder::new(&buffer).unwrap();
+    assert_eq!(decoder.filter_range(..=-999_900).unwrap().len(), {
+        values.iter().filter(|i| **i <= -999_900).count()
+    });
+
+    let truncated = &buffer[..buffer.len() - 10];
+    let mut decoder = SliceDecoder::new(truncated).unwrap();
+    assert!(decoder.read_integers().is_err());
+}
@@ -18,6 +18,10 @@ macro_rules! pack_unpack_with_bits {
             const NUM_BITS: usize = $n;
             const NUM_BYTES_PER_BLOCK: usize = NUM_BITS * BLOCK_LEN / 8;
 
+            /// # Safety
+            ///
+            /// `input_arr` must hold exactly `BLOCK_LEN` values and `output_arr` at least
+            /// `NUM_BYTES_PER_BLOCK` bytes; both are asserted before any pointer access.
             pub unsafe fn pack(input_arr: &[u64], output_arr: &mut [u8]) -> usize {
                 assert_eq!(
                     input_arr.len(),
@@ -80,6 +84,10 @@ macro_rules! pack_unpack_with_bits {
                 NUM_BYTES_PER_BLOCK
             }
 
+            /// # Safety
+            ///
+            /// `compressed` must hold at least `NUM_BYTES_PER_BLOCK` bytes (asserted) and
+            /// `output` must have room for `BLOCK_LEN` values.
             pub unsafe fn unpack(compressed: &[u8], output: &mut [u64]) -> usize {
                 assert!(
                     compressed.len() >= NUM_BYTES_PER_BLOCK,
@@ -150,6 +158,10 @@ mod pack_unpack_64 {
     const NUM_BITS: usize = 64;
     const NUM_BYTES_PER_BLOCK: usize = NUM_BITS * BLOCK_LEN / 8;
 
+    /// # Safety
+    ///
+    /// `input_arr` must hold exactly `BLOCK_LEN` values and `output_arr` at least
+    /// `NUM_BYTES_PER_BLOCK` bytes; both are asserted before any pointer access.
     pub unsafe fn pack(input_arr: &[u64], output_arr: &mut [u8]) -> usize {
         assert_eq!(
             input_arr.len(),
@@ -170,8 +182,8 @@ mod pack_unpack_64 {
         let output_ptr = output_arr.as_mut_ptr() as *mut DataType;
         unroll! {
             for i in 0..64 {
-                let input_offset_ptr = input_ptr.offset(i as isize);
-                let output_offset_ptr = output_ptr.offset(i as isize);
+                let input_offset_ptr = input_ptr.add(i);
+                let output_offset_ptr = output_ptr.add(i);
                 let input_register = load_unaligned(input_offset_ptr);
                 let output_register = input_register;
                 store_unaligned(output_offset_ptr, output_register);
@@ -180,6 +192,10 @@ mod pack_unpack_64 {
         NUM_BYTES_PER_BLOCK
     }
 
+    /// # Safety
+    ///
+    /// `compressed` must hold at least `NUM_BYTES_PER_BLOCK` bytes (asserted) and
+    /// `output` must have room for `BLOCK_LEN` values.
     pub unsafe fn unpack(compressed: &[u8], output: &mut [u64]) -> usize {
         assert!(
             compressed.len() >= NUM_BYTES_PER_BLOCK,
@@ -190,7 +206,7 @@ mod pack_unpack_64 {
         let input_ptr = compressed.as_ptr() as *const DataType;
         let mut store = Store::new(output.as_mut_ptr() as *mut DataType);
         for i in 0..64 {
-            let input_offset_ptr = input_ptr.offset(i as isize);
+            let input_offset_ptr = input_ptr.add(i);
             let in_register: DataType = load_unaligned(input_offset_ptr);
             store.process(in_register);
         }
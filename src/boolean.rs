@@ -0,0 +1,261 @@
+//! Boolean column codec.
+//!
+//! Values are both bit packed with one bit each and run-length encoded with
+//! [`rle`](crate::rle), and whichever is smaller is written. The stream starts with a
+//! byte naming the encoding, the value count and the payload length.
+//!
+//! With a bit width of 1, [`bitpacker64`] lays out 64 values as one little-endian `u64`
+//! with the first value in the least significant bit, so the plain payload doubles as the
+//! words of a [`Bitmap`].
+
+use crate::bitpacker64::BLOCK_LEN;
+use crate::delta_encoding_64::read_vlq;
+use crate::{bitpacker64, rle};
+use std::io::{Read, Write};
+use vlq::WriteVlqExt;
+
+const PLAIN: u8 = 0;
+const RLE: u8 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_words(words: Vec<u64>, len: usize) -> Self {
+        assert!(
+            words.len() * 64 >= len,
+            "{} words can't hold {} bits",
+            words.len(),
+            len
+        );
+        let mut bitmap = Self { words, len };
+        bitmap.words.truncate(len.div_ceil(64));
+        if !len.is_multiple_of(64) {
+            *bitmap.words.last_mut().unwrap() &= (1u64 << (len % 64)) - 1;
+        }
+        bitmap
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        if value {
+            *self.words.last_mut().unwrap() |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        (index < self.len).then(|| self.words[index / 64] & (1 << (index % 64)) != 0)
+    }
+
+    /// Number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|index| self.words[index / 64] & (1 << (index % 64)) != 0)
+    }
+
+    /// Positions of the set bits in ascending order, skipping a whole word at a time.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, word)| {
+                let mut word = *word;
+                std::iter::from_fn(move || {
+                    if word == 0 {
+                        return None;
+                    }
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(word_index * 64 + bit)
+                })
+            })
+    }
+}
+
+impl FromIterator<bool> for Bitmap {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bitmap = Bitmap::new();
+        iter.into_iter().for_each(|value| bitmap.push(value));
+        bitmap
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    pub total_count: usize,
+    pub mini_block: [u64; BLOCK_LEN],
+    pub packed: Vec<u8>,
+    pub rle: rle::Encoder,
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self {
+            total_count: 0,
+            mini_block: [0; BLOCK_LEN],
+            packed: vec![],
+            rle: rle::Encoder::new(1),
+        }
+    }
+
+    pub fn write_value(&mut self, value: bool) -> anyhow::Result<()> {
+        self.mini_block[self.total_count % BLOCK_LEN] = value as u64;
+        self.total_count += 1;
+        if self.total_count.is_multiple_of(BLOCK_LEN) {
+            self.pack_mini_block();
+        }
+        self.rle.write_value(value as u32)
+    }
+
+    fn pack_mini_block(&mut self) {
+        let mut packed = [0u8; BLOCK_LEN / 8];
+        bitpacker64::pack(&self.mini_block, &mut packed, 1);
+        self.packed.extend_from_slice(&packed);
+        self.mini_block.fill(0);
+    }
+
+    pub fn write<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        if !self.total_count.is_multiple_of(BLOCK_LEN) {
+            self.pack_mini_block();
+            self.packed.truncate(self.total_count.div_ceil(8));
+        }
+        let mut rle_bytes = vec![];
+        self.rle.write(&mut rle_bytes)?;
+
+        let (encoding, payload) = if rle_bytes.len() < self.packed.len() {
+            (RLE, rle_bytes)
+        } else {
+            (PLAIN, std::mem::take(&mut self.packed))
+        };
+
+        io.write_all(&[encoding]).map_err(|e| anyhow::anyhow!(e))?;
+        io.write_vlq(self.total_count as i64)
+            .map_err(|e| anyhow::anyhow!("Total size failed {}, {:?}", self.total_count, e))?;
+        io.write_vlq(payload.len() as i64)
+            .map_err(|e| anyhow::anyhow!("Payload size failed {}, {:?}", payload.len(), e))?;
+        io.write_all(&payload).map_err(|e| anyhow::anyhow!(e))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    pub bitmap: Bitmap,
+    pub values_read: usize,
+}
+
+impl Decoder {
+    pub fn new<T: Read>(mut io: T) -> anyhow::Result<Self> {
+        let mut encoding = [0u8; 1];
+        io.read_exact(&mut encoding)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let total_count = usize::try_from(read_vlq(&mut io)?)?;
+        let payload_len = read_vlq(&mut io)?;
+
+        // Read through `take` so a corrupted length can't allocate more than the input has.
+        let mut payload = vec![];
+        io.take(payload_len)
+            .read_to_end(&mut payload)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if payload.len() as u64 != payload_len {
+            anyhow::bail!(
+                "Boolean payload truncated {} of {}",
+                payload.len(),
+                payload_len
+            )
+        }
+
+        let bitmap = match encoding[0] {
+            PLAIN => {
+                if payload.len() != total_count.div_ceil(8) {
+                    anyhow::bail!("Bit packed booleans truncated {}", payload.len())
+                }
+                payload.resize(total_count.div_ceil(64) * 8, 0);
+                let words = payload
+                    .chunks_exact(8)
+                    .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+                    .collect();
+                Bitmap::from_words(words, total_count)
+            }
+            RLE => {
+                // Decoded in batches, so only values the payload holds are allocated.
+                let mut decoder = rle::Decoder::new(&payload, 1);
+                let mut batch = vec![0u32; BLOCK_LEN];
+                let mut values = vec![];
+                while values.len() < total_count {
+                    let count = batch.len().min(total_count - values.len());
+                    if decoder.get_batch(&mut batch[..count])? != count {
+                        anyhow::bail!("RLE booleans truncated")
+                    }
+                    values.extend(batch[..count].iter().map(|value| *value != 0));
+                }
+                values.into_iter().collect()
+            }
+            other => anyhow::bail!("Unknown boolean encoding {}", other),
+        };
+
+        Ok(Self {
+            bitmap,
+            values_read: 0,
+        })
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.bitmap.len()
+    }
+
+    pub fn all_read(&self) -> bool {
+        self.values_read == self.bitmap.len()
+    }
+
+    pub fn read_value(&mut self) -> anyhow::Result<bool> {
+        let value = self
+            .bitmap
+            .get(self.values_read)
+            .ok_or_else(|| anyhow::anyhow!("All values read"))?;
+        self.values_read += 1;
+        Ok(value)
+    }
+
+    pub fn read_values(&mut self) -> anyhow::Result<Vec<bool>> {
+        let mut values = Vec::with_capacity(self.bitmap.len() - self.values_read);
+        while !self.all_read() {
+            values.push(self.read_value()?);
+        }
+        Ok(values)
+    }
+}
@@ -0,0 +1,218 @@
+//! Parquet's `BYTE_STREAM_SPLIT` encoding for floats and other fixed-width values.
+//!
+//! Byte `k` of every value is written to stream `k`, and the streams are concatenated.
+//! This doesn't shrink the data by itself, but the exponent and high mantissa bytes of
+//! floats end up next to each other, which general purpose compressors exploit well.
+
+use std::io::Write;
+use std::marker::PhantomData;
+
+/// Values are transposed in tiles of this many, so the inner loops work on fixed-size
+/// arrays the compiler can vectorize.
+const TILE_LEN: usize = 64;
+
+/// A value with a fixed little-endian byte representation.
+pub trait FixedWidth: Copy {
+    const WIDTH: usize;
+
+    fn write_le_bytes(&self, output: &mut Vec<u8>);
+
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+}
+
+macro_rules! fixed_width {
+    ($type:ty) => {
+        impl FixedWidth for $type {
+            const WIDTH: usize = std::mem::size_of::<$type>();
+
+            fn write_le_bytes(&self, output: &mut Vec<u8>) {
+                output.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                <$type>::from_le_bytes(bytes.try_into().unwrap())
+            }
+        }
+    };
+}
+
+fixed_width!(f32);
+fixed_width!(f64);
+fixed_width!(i32);
+fixed_width!(i64);
+fixed_width!(i128);
+fixed_width!(u32);
+fixed_width!(u64);
+fixed_width!(u128);
+
+fn check_width(len: usize, width: usize) -> anyhow::Result<usize> {
+    if width == 0 || !len.is_multiple_of(width) {
+        anyhow::bail!(
+            "{} bytes is not a whole number of {} byte values",
+            len,
+            width
+        )
+    }
+    Ok(len / width)
+}
+
+/// Scatters byte `k` of each `width` byte value in `values` into stream `k` of `output`.
+pub fn encode(values: &[u8], width: usize, output: &mut [u8]) -> anyhow::Result<()> {
+    check_width(values.len(), width)?;
+    if output.len() != values.len() {
+        anyhow::bail!(
+            "Output of {} bytes for {} bytes",
+            output.len(),
+            values.len()
+        )
+    }
+
+    match width {
+        4 => split::<4>(values, output),
+        8 => split::<8>(values, output),
+        16 => split::<16>(values, output),
+        _ => split_dynamic(values, width, output),
+    }
+    Ok(())
+}
+
+/// Gathers the `width` byte streams in `streams` back into whole values.
+pub fn decode(streams: &[u8], width: usize, output: &mut [u8]) -> anyhow::Result<()> {
+    check_width(streams.len(), width)?;
+    if output.len() != streams.len() {
+        anyhow::bail!(
+            "Output of {} bytes for {} bytes",
+            output.len(),
+            streams.len()
+        )
+    }
+
+    match width {
+        4 => join::<4>(streams, output),
+        8 => join::<8>(streams, output),
+        16 => join::<16>(streams, output),
+        _ => join_dynamic(streams, width, output),
+    }
+    Ok(())
+}
+
+fn split<const W: usize>(values: &[u8], output: &mut [u8]) {
+    let count = values.len() / W;
+    let mut tile = [[0u8; TILE_LEN]; W];
+
+    for (tile_index, chunk) in values.chunks(W * TILE_LEN).enumerate() {
+        let start = tile_index * TILE_LEN;
+        let len = chunk.len() / W;
+        for (i, value) in chunk.chunks_exact(W).enumerate() {
+            for k in 0..W {
+                tile[k][i] = value[k];
+            }
+        }
+        for (k, stream) in tile.iter().enumerate() {
+            output[k * count + start..k * count + start + len].copy_from_slice(&stream[..len]);
+        }
+    }
+}
+
+fn join<const W: usize>(streams: &[u8], output: &mut [u8]) {
+    let count = streams.len() / W;
+    let mut tile = [[0u8; TILE_LEN]; W];
+
+    for (tile_index, chunk) in output.chunks_mut(W * TILE_LEN).enumerate() {
+        let start = tile_index * TILE_LEN;
+        let len = chunk.len() / W;
+        for (k, stream) in tile.iter_mut().enumerate() {
+            stream[..len].copy_from_slice(&streams[k * count + start..k * count + start + len]);
+        }
+        for (i, value) in chunk.chunks_exact_mut(W).enumerate() {
+            for k in 0..W {
+                value[k] = tile[k][i];
+            }
+        }
+    }
+}
+
+fn split_dynamic(values: &[u8], width: usize, output: &mut [u8]) {
+    let count = values.len() / width;
+    for (i, value) in values.chunks_exact(width).enumerate() {
+        for (k, byte) in value.iter().enumerate() {
+            output[k * count + i] = *byte;
+        }
+    }
+}
+
+fn join_dynamic(streams: &[u8], width: usize, output: &mut [u8]) {
+    let count = streams.len() / width;
+    for (i, value) in output.chunks_exact_mut(width).enumerate() {
+        for (k, byte) in value.iter_mut().enumerate() {
+            *byte = streams[k * count + i];
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Encoder<T: FixedWidth> {
+    pub values: Vec<u8>,
+
+    value_type: PhantomData<T>,
+}
+
+impl<T: FixedWidth> Default for Encoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: FixedWidth> Encoder<T> {
+    pub fn new() -> Self {
+        Self {
+            values: vec![],
+            value_type: PhantomData,
+        }
+    }
+
+    pub fn write_value(&mut self, value: T) {
+        value.write_le_bytes(&mut self.values);
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.values.len() / T::WIDTH
+    }
+
+    pub fn write<W: Write>(&mut self, io: &mut W) -> anyhow::Result<()> {
+        let values = std::mem::take(&mut self.values);
+        let mut streams = vec![0u8; values.len()];
+        encode(&values, T::WIDTH, &mut streams)?;
+        io.write_all(&streams).map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Decoder<'a, T: FixedWidth> {
+    pub streams: &'a [u8],
+
+    value_type: PhantomData<T>,
+}
+
+impl<'a, T: FixedWidth> Decoder<'a, T> {
+    pub fn new(streams: &'a [u8]) -> anyhow::Result<Self> {
+        check_width(streams.len(), T::WIDTH)?;
+        Ok(Self {
+            streams,
+            value_type: PhantomData,
+        })
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.streams.len() / T::WIDTH
+    }
+
+    pub fn read_values(&self) -> anyhow::Result<Vec<T>> {
+        let mut values = vec![0u8; self.streams.len()];
+        decode(self.streams, T::WIDTH, &mut values)?;
+        Ok(values
+            .chunks_exact(T::WIDTH)
+            .map(T::from_le_bytes)
+            .collect())
+    }
+}
@@ -0,0 +1,348 @@
+//! A file holding many encoded columns.
+//!
+//! ```text
+//! magic, version
+//! column data, back to back
+//! directory: column count, then per column its name, value type, encoding, offset,
+//!            length and CRC32C
+//! footer:    directory offset (u64 LE), directory CRC32C (u32 LE), magic
+//! ```
+//!
+//! The footer has a fixed size, so a reader finds the directory from the end of the file
+//! and only reads the columns it is asked for.
+
+use crate::delta_encoding_64::{self, read_vlq, BytesDecoder};
+use bytes::Bytes;
+use std::io::{Read, Seek, SeekFrom, Write};
+use vlq::WriteVlqExt;
+
+pub const MAGIC: &[u8; 4] = b"ENCC";
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: u64 = MAGIC.len() as u64 + 1;
+const FOOTER_LEN: u64 = 8 + 4 + MAGIC.len() as u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    Boolean,
+    Binary,
+}
+
+impl ValueType {
+    fn tag(self) -> u8 {
+        match self {
+            ValueType::Int32 => 0,
+            ValueType::Int64 => 1,
+            ValueType::Float32 => 2,
+            ValueType::Float64 => 3,
+            ValueType::Boolean => 4,
+            ValueType::Binary => 5,
+        }
+    }
+
+    fn from_tag(tag: u8) -> anyhow::Result<Self> {
+        Ok(match tag {
+            0 => ValueType::Int32,
+            1 => ValueType::Int64,
+            2 => ValueType::Float32,
+            3 => ValueType::Float64,
+            4 => ValueType::Boolean,
+            5 => ValueType::Binary,
+            other => anyhow::bail!("Unknown value type {}", other),
+        })
+    }
+}
+
+/// The codec a column was written with, naming the module that reads it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// [`delta_encoding_64::Encoder`]
+    Delta,
+    /// [`delta_encoding_64::NullableEncoder`]
+    NullableDelta,
+    /// [`crate::frame_of_reference_64`]
+    FrameOfReference,
+    /// [`crate::boolean`]
+    Boolean,
+    /// [`crate::byte_stream_split`]
+    ByteStreamSplit,
+    /// [`crate::delta_length_byte_array`]
+    DeltaLengthByteArray,
+    /// [`crate::delta_byte_array`]
+    DeltaByteArray,
+    /// [`crate::dictionary`]
+    Dictionary,
+    /// [`crate::elias_fano`]
+    EliasFano,
+}
+
+impl Encoding {
+    fn tag(self) -> u8 {
+        match self {
+            Encoding::Delta => 0,
+            Encoding::NullableDelta => 1,
+            Encoding::FrameOfReference => 2,
+            Encoding::Boolean => 3,
+            Encoding::ByteStreamSplit => 4,
+            Encoding::DeltaLengthByteArray => 5,
+            Encoding::DeltaByteArray => 6,
+            Encoding::Dictionary => 7,
+            Encoding::EliasFano => 8,
+        }
+    }
+
+    fn from_tag(tag: u8) -> anyhow::Result<Self> {
+        Ok(match tag {
+            0 => Encoding::Delta,
+            1 => Encoding::NullableDelta,
+            2 => Encoding::FrameOfReference,
+            3 => Encoding::Boolean,
+            4 => Encoding::ByteStreamSplit,
+            5 => Encoding::DeltaLengthByteArray,
+            6 => Encoding::DeltaByteArray,
+            7 => Encoding::Dictionary,
+            8 => Encoding::EliasFano,
+            other => anyhow::bail!("Unknown encoding {}", other),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub value_type: ValueType,
+    pub encoding: Encoding,
+    /// Position of the column data from the start of the container.
+    pub offset: u64,
+    pub len: u64,
+    /// CRC32C of the column data.
+    pub checksum: u32,
+}
+
+impl ColumnInfo {
+    fn write<W: Write>(&self, io: &mut W) -> anyhow::Result<()> {
+        io.write_vlq(self.name.len() as u64)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        io.write_all(self.name.as_bytes())
+            .map_err(|e| anyhow::anyhow!(e))?;
+        io.write_all(&[self.value_type.tag(), self.encoding.tag()])
+            .map_err(|e| anyhow::anyhow!(e))?;
+        io.write_vlq(self.offset).map_err(|e| anyhow::anyhow!(e))?;
+        io.write_vlq(self.len).map_err(|e| anyhow::anyhow!(e))?;
+        io.write_all(&self.checksum.to_le_bytes())
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    fn read<R: Read>(io: &mut R) -> anyhow::Result<Self> {
+        let name_len = read_vlq(io)?;
+        let mut name = vec![];
+        io.take(name_len)
+            .read_to_end(&mut name)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if name.len() as u64 != name_len {
+            anyhow::bail!("Column name truncated")
+        }
+        let name = String::from_utf8(name).map_err(|e| anyhow::anyhow!(e))?;
+
+        let mut tags = [0u8; 2];
+        io.read_exact(&mut tags).map_err(|e| anyhow::anyhow!(e))?;
+        let offset = read_vlq(io)?;
+        let len = read_vlq(io)?;
+        let mut checksum = [0u8; 4];
+        io.read_exact(&mut checksum)
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        Ok(Self {
+            name,
+            value_type: ValueType::from_tag(tags[0])?,
+            encoding: Encoding::from_tag(tags[1])?,
+            offset,
+            len,
+            checksum: u32::from_le_bytes(checksum),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ContainerWriter<W: Write> {
+    pub io: W,
+    pub columns: Vec<ColumnInfo>,
+
+    position: u64,
+}
+
+impl<W: Write> ContainerWriter<W> {
+    pub fn new(mut io: W) -> anyhow::Result<Self> {
+        io.write_all(MAGIC).map_err(|e| anyhow::anyhow!(e))?;
+        io.write_all(&[VERSION]).map_err(|e| anyhow::anyhow!(e))?;
+        Ok(Self {
+            io,
+            columns: vec![],
+            position: HEADER_LEN,
+        })
+    }
+
+    /// Appends a column of already encoded `data`.
+    pub fn write_column(
+        &mut self,
+        name: &str,
+        value_type: ValueType,
+        encoding: Encoding,
+        data: &[u8],
+    ) -> anyhow::Result<()> {
+        if self.columns.iter().any(|column| column.name == name) {
+            anyhow::bail!("Duplicate column {}", name)
+        }
+        self.io.write_all(data).map_err(|e| anyhow::anyhow!(e))?;
+        self.columns.push(ColumnInfo {
+            name: name.to_string(),
+            value_type,
+            encoding,
+            offset: self.position,
+            len: data.len() as u64,
+            checksum: crc32c::crc32c(data),
+        });
+        self.position += data.len() as u64;
+        Ok(())
+    }
+
+    /// Writes the values of `encoder` as an `Int64` column.
+    pub fn write_delta_column(
+        &mut self,
+        name: &str,
+        encoder: &mut delta_encoding_64::Encoder,
+    ) -> anyhow::Result<()> {
+        let data = encoder.finish()?;
+        self.write_column(name, ValueType::Int64, Encoding::Delta, &data)
+    }
+
+    /// Writes the directory and footer and returns `io`.
+    pub fn finish(mut self) -> anyhow::Result<W> {
+        let mut directory = vec![];
+        directory
+            .write_vlq(self.columns.len() as u64)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        for column in &self.columns {
+            column.write(&mut directory)?;
+        }
+
+        let mut footer = Vec::with_capacity(FOOTER_LEN as usize);
+        footer.extend_from_slice(&self.position.to_le_bytes());
+        footer.extend_from_slice(&crc32c::crc32c(&directory).to_le_bytes());
+        footer.extend_from_slice(MAGIC);
+
+        self.io
+            .write_all(&directory)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        self.io.write_all(&footer).map_err(|e| anyhow::anyhow!(e))?;
+        self.io.flush().map_err(|e| anyhow::anyhow!(e))?;
+        Ok(self.io)
+    }
+}
+
+#[derive(Debug)]
+pub struct ContainerReader<R: Read + Seek> {
+    pub io: R,
+    pub version: u8,
+    pub columns: Vec<ColumnInfo>,
+}
+
+impl<R: Read + Seek> ContainerReader<R> {
+    /// Reads the header, footer and directory, but no column data.
+    pub fn new(mut io: R) -> anyhow::Result<Self> {
+        let mut header = [0u8; HEADER_LEN as usize];
+        io.seek(SeekFrom::Start(0))
+            .map_err(|e| anyhow::anyhow!(e))?;
+        io.read_exact(&mut header).map_err(|e| anyhow::anyhow!(e))?;
+        if &header[..MAGIC.len()] != MAGIC {
+            anyhow::bail!("Not a container, magic {:?}", &header[..MAGIC.len()])
+        }
+        let version = header[MAGIC.len()];
+        if version != VERSION {
+            anyhow::bail!("Unsupported container version {}", version)
+        }
+
+        let end = io.seek(SeekFrom::End(0)).map_err(|e| anyhow::anyhow!(e))?;
+        if end < HEADER_LEN + FOOTER_LEN {
+            anyhow::bail!("Container truncated, {} bytes", end)
+        }
+        let mut footer = [0u8; FOOTER_LEN as usize];
+        io.seek(SeekFrom::Start(end - FOOTER_LEN))
+            .map_err(|e| anyhow::anyhow!(e))?;
+        io.read_exact(&mut footer).map_err(|e| anyhow::anyhow!(e))?;
+        if &footer[12..] != MAGIC {
+            anyhow::bail!("Container truncated, no footer")
+        }
+        let directory_offset = u64::from_le_bytes(footer[..8].try_into().unwrap());
+        let directory_checksum = u32::from_le_bytes(footer[8..12].try_into().unwrap());
+        if directory_offset < HEADER_LEN || directory_offset > end - FOOTER_LEN {
+            anyhow::bail!("Invalid directory offset {}", directory_offset)
+        }
+
+        let mut directory = vec![0u8; (end - FOOTER_LEN - directory_offset) as usize];
+        io.seek(SeekFrom::Start(directory_offset))
+            .map_err(|e| anyhow::anyhow!(e))?;
+        io.read_exact(&mut directory)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if crc32c::crc32c(&directory) != directory_checksum {
+            anyhow::bail!("Directory checksum mismatch")
+        }
+
+        let mut directory = directory.as_slice();
+        let count = read_vlq(&mut directory)?;
+        let mut columns = vec![];
+        for _ in 0..count {
+            let column = ColumnInfo::read(&mut directory)?;
+            let column_end = column.offset.checked_add(column.len);
+            if column.offset < HEADER_LEN || column_end.is_none_or(|end| end > directory_offset) {
+                anyhow::bail!("Column {} out of bounds", column.name)
+            }
+            columns.push(column);
+        }
+        if !directory.is_empty() {
+            anyhow::bail!("{} bytes after the directory", directory.len())
+        }
+
+        Ok(Self {
+            io,
+            version,
+            columns,
+        })
+    }
+
+    pub fn column(&self, name: &str) -> Option<&ColumnInfo> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Reads the data of column `name` and verifies its checksum.
+    pub fn read_column(&mut self, name: &str) -> anyhow::Result<Bytes> {
+        let column = self
+            .column(name)
+            .ok_or_else(|| anyhow::anyhow!("No column {}", name))?
+            .clone();
+
+        let mut data = vec![0u8; column.len as usize];
+        self.io
+            .seek(SeekFrom::Start(column.offset))
+            .map_err(|e| anyhow::anyhow!(e))?;
+        self.io
+            .read_exact(&mut data)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if crc32c::crc32c(&data) != column.checksum {
+            anyhow::bail!("Column {} checksum mismatch", name)
+        }
+        Ok(Bytes::from(data))
+    }
+
+    /// Opens a column written with [`Encoding::Delta`].
+    pub fn delta_decoder(&mut self, name: &str) -> anyhow::Result<BytesDecoder> {
+        match self.column(name).map(|column| column.encoding) {
+            Some(Encoding::Delta) | None => BytesDecoder::new(self.read_column(name)?),
+            Some(encoding) => anyhow::bail!("Column {} is encoded with {:?}", name, encoding),
+        }
+    }
+}
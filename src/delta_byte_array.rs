@@ -0,0 +1,130 @@
+//! Parquet's `DELTA_BYTE_ARRAY` encoding, also known as front coding.
+//!
+//! Every value is split into the length of the prefix it shares with the previous value
+//! and the remaining suffix. The prefix lengths are written as a `DELTA_BINARY_PACKED`
+//! stream, followed by the suffixes in
+//! [`delta_length_byte_array`](crate::delta_length_byte_array) encoding.
+
+use crate::delta_encoding_64::{Format, PARQUET_MINI_BLOCKS};
+use crate::{delta_encoding_64, delta_length_byte_array};
+use std::io::Write;
+
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    pub prefix_lengths: delta_encoding_64::Encoder,
+    pub suffixes: delta_length_byte_array::Encoder,
+    pub previous_value: Vec<u8>,
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self {
+            prefix_lengths: delta_encoding_64::Encoder::new(PARQUET_MINI_BLOCKS)
+                .with_format(Format::Parquet),
+            suffixes: delta_length_byte_array::Encoder::new(),
+            previous_value: vec![],
+        }
+    }
+
+    pub fn write_value(&mut self, value: &[u8]) -> anyhow::Result<()> {
+        let prefix_len = self
+            .previous_value
+            .iter()
+            .zip(value)
+            .take_while(|(previous, current)| previous == current)
+            .count();
+
+        self.prefix_lengths.write_integer(prefix_len as i64)?;
+        self.suffixes.write_value(&value[prefix_len..])?;
+
+        self.previous_value.clear();
+        self.previous_value.extend_from_slice(value);
+        Ok(())
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.prefix_lengths.total_count
+    }
+
+    pub fn write<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        self.prefix_lengths.flush()?;
+        self.prefix_lengths.write(io)?;
+        self.suffixes.write(io)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Decoder<'a> {
+    pub prefix_lengths: Vec<usize>,
+    pub suffixes: delta_length_byte_array::Decoder<'a>,
+    pub previous_value: Vec<u8>,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(mut data: &'a [u8]) -> anyhow::Result<Self> {
+        let prefix_lengths = delta_encoding_64::Decoder::with_format(&mut data, Format::Parquet)?
+            .read_integers()?
+            .into_iter()
+            .map(|len| {
+                usize::try_from(len).map_err(|_| anyhow::anyhow!("Invalid prefix length {}", len))
+            })
+            .collect::<anyhow::Result<Vec<usize>>>()?;
+
+        let suffixes = delta_length_byte_array::Decoder::new(data)?;
+        if suffixes.total_count() != prefix_lengths.len() {
+            anyhow::bail!(
+                "Prefix count {} does not match suffix count {}",
+                prefix_lengths.len(),
+                suffixes.total_count()
+            )
+        }
+
+        Ok(Self {
+            prefix_lengths,
+            suffixes,
+            previous_value: vec![],
+        })
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.prefix_lengths.len()
+    }
+
+    pub fn all_read(&self) -> bool {
+        self.suffixes.all_read()
+    }
+
+    pub fn read_value(&mut self) -> anyhow::Result<Vec<u8>> {
+        let prefix_len = self
+            .prefix_lengths
+            .get(self.suffixes.values_read)
+            .copied()
+            .unwrap_or_default();
+        let suffix = self.suffixes.read_value()?;
+        if prefix_len > self.previous_value.len() {
+            anyhow::bail!(
+                "Prefix length {} exceeds previous value length {}",
+                prefix_len,
+                self.previous_value.len()
+            )
+        }
+
+        self.previous_value.truncate(prefix_len);
+        self.previous_value.extend_from_slice(suffix);
+        Ok(self.previous_value.clone())
+    }
+
+    pub fn read_values(&mut self) -> anyhow::Result<Vec<Vec<u8>>> {
+        let mut values = Vec::with_capacity(self.total_count());
+        while !self.all_read() {
+            values.push(self.read_value()?);
+        }
+        Ok(values)
+    }
+}
@@ -1,16 +1,163 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "tokio")]
+pub mod async_io;
+mod checksum;
 pub mod delta_vec;
-
-use crate::bitpacker64::{num_bits, BLOCK_LEN};
-use crate::{bitpacker64, zig_zag};
+pub mod input;
+mod layout;
+pub mod nullable;
+pub mod serde_delta;
+mod stats;
+pub mod streaming;
+
+use crate::bitpacker64::BLOCK_LEN;
+#[cfg(feature = "tokio")]
+pub use async_io::{AsyncDecoder, AsyncEncoder};
+use bytes::{BufMut, Bytes, BytesMut};
+pub use checksum::ChecksumMismatch;
+use checksum::{read_checksum, write_checksum, ChecksumReader};
 pub use delta_vec::{DeltaVec, DeltaVecDecimal};
+pub use input::{BytesInput, Input, ReadInput};
+pub(crate) use layout::{
+    check_bit_widths, decode_zig_zag_var_int, packed_len, read_bit_widths, read_mini_block,
+    read_var_int, read_vlq, unpack_mini_block, write_mini_blocks, write_padded_var_int,
+    write_var_int, write_zig_zag_var_int, Header, HEADER_VERSION, PARQUET_MINI_BLOCKS,
+};
+pub use nullable::{NullableDecoder, NullableEncoder};
+pub use stats::Stats;
 use std::collections::VecDeque;
 use std::io::{Cursor, Read, Write};
-use vlq::{ReadVlqExt, WriteVlqExt};
+use std::ops::{Bound, RangeBounds};
+pub use streaming::StreamingEncoder;
+
+const MODE_MASK: i64 = 0b11;
+/// Set when every block is prefixed with a [`BlockIndex`].
+const BLOCK_INDEX_FLAG: i64 = 0b100;
+/// Set when the header is followed by the [`Stats`] of the stream and of every block.
+const STATS_FLAG: i64 = 0b1000;
+/// Set when the header and every block carry a CRC32C.
+const CHECKSUM_FLAG: i64 = 0b10000;
+const KNOWN_FLAGS: i64 = MODE_MASK | BLOCK_INDEX_FLAG | STATS_FLAG | CHECKSUM_FLAG;
+
+/// The byte layout of a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// The crate's own layout, using `vlq` varints and carrying the stream options in
+    /// the header.
+    #[default]
+    Native,
+    /// Parquet's `DELTA_BINARY_PACKED` layout, using ULEB128 varints. Only supports
+    /// [`Mode::Delta`] and needs an even number of mini blocks.
+    Parquet,
+}
+
+/// How consecutive values are turned into the integers that get bit packed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Stores the difference between each value and the previous one.
+    #[default]
+    Delta,
+    /// Stores the difference between consecutive deltas, so regularly spaced series
+    /// (e.g. timestamps) collapse to blocks of zero-width mini blocks.
+    DeltaOfDelta,
+}
+
+/// Prefix written before every block when the block index is enabled, so readers can
+/// tell which values a block holds and skip its packed mini blocks without unpacking them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BlockIndex {
+    packed_len: usize,
+    min: i64,
+    max: i64,
+    last_value: i64,
+    last_delta: i64,
+}
+
+impl BlockIndex {
+    fn write<T: Write>(&self, io: &mut T, mode: Mode) -> anyhow::Result<()> {
+        write_var_int(io, Format::Native, self.packed_len as u64)?;
+        write_zig_zag_var_int(io, Format::Native, self.min)?;
+        write_zig_zag_var_int(io, Format::Native, self.max)?;
+        write_zig_zag_var_int(io, Format::Native, self.last_value)?;
+        if mode == Mode::DeltaOfDelta {
+            write_zig_zag_var_int(io, Format::Native, self.last_delta)?;
+        }
+        Ok(())
+    }
+
+    fn read<T: Read>(io: &mut T, mode: Mode) -> anyhow::Result<Self> {
+        let packed_len = read_var_int(io, Format::Native)? as usize;
+        let min = decode_zig_zag_var_int(io, Format::Native)?;
+        let max = decode_zig_zag_var_int(io, Format::Native)?;
+        let last_value = decode_zig_zag_var_int(io, Format::Native)?;
+        let last_delta = match mode {
+            Mode::Delta => 0,
+            Mode::DeltaOfDelta => decode_zig_zag_var_int(io, Format::Native)?,
+        };
+        if min > max {
+            anyhow::bail!("Invalid block index {} > {}", min, max)
+        }
+        Ok(Self {
+            packed_len,
+            min,
+            max,
+            last_value,
+            last_delta,
+        })
+    }
+
+    fn overlaps<R: RangeBounds<i64>>(&self, range: &R) -> bool {
+        let above_start = match range.start_bound() {
+            Bound::Included(start) => self.max >= *start,
+            Bound::Excluded(start) => self.max > *start,
+            Bound::Unbounded => true,
+        };
+        let below_end = match range.end_bound() {
+            Bound::Included(end) => self.min <= *end,
+            Bound::Excluded(end) => self.min < *end,
+            Bound::Unbounded => true,
+        };
+        above_start && below_end
+    }
+
+    fn within<R: RangeBounds<i64>>(&self, range: &R) -> bool {
+        range.contains(&self.min) && range.contains(&self.max)
+    }
+}
+
+impl Mode {
+    fn header_bits(self) -> i64 {
+        match self {
+            Mode::Delta => 0,
+            Mode::DeltaOfDelta => 1,
+        }
+    }
+
+    fn from_header_bits(bits: i64) -> anyhow::Result<Self> {
+        match bits & MODE_MASK {
+            0 => Ok(Mode::Delta),
+            1 => Ok(Mode::DeltaOfDelta),
+            other => anyhow::bail!("Unknown delta mode {}", other),
+        }
+    }
+
+    /// Number of values stored in the header rather than in blocks.
+    fn header_values(self) -> usize {
+        match self {
+            Mode::Delta => 1,
+            Mode::DeltaOfDelta => 2,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Encoder {
     pub first_value: i64,
     pub previous_value: i64,
+    pub first_delta: i64,
+    pub previous_delta: i64,
+    /// Bit widths of the mini blocks of the last flushed block.
     pub bit_widths: Vec<u8>,
     pub total_count: usize,
     pub min_delta: i64,
@@ -18,20 +165,17 @@ pub struct Encoder {
     pub pos: usize,
     pub blocks_buffer: Cursor<Vec<u8>>,
 
+    mode: Mode,
+    format: Format,
     block_size: usize,
     mini_blocks: usize,
-    mini_block_size: usize,
-}
-
-fn write_zig_zag_var_int<T: Write>(buffer: &mut T, value: i64) -> anyhow::Result<()> {
-    buffer
-        .write_vlq(zig_zag::encode64(value))
-        .map_err(|e| anyhow::anyhow!("write_zig_zag_var_int failed {}, {:?}", value, e))
-}
-
-fn decode_zig_zag_var_int<T: Read>(buffer: &mut T) -> anyhow::Result<i64> {
-    let i = buffer.read_vlq().map_err(|e| anyhow::anyhow!(e))?;
-    Ok(zig_zag::decode64(i))
+    block_index: bool,
+    stats_section: bool,
+    checksums: bool,
+    versioned: bool,
+    block: Option<Stats>,
+    block_stats: Vec<Stats>,
+    stats: Option<Stats>,
 }
 
 impl Encoder {
@@ -42,19 +186,128 @@ impl Encoder {
         Self {
             first_value: 0,
             previous_value: 0,
+            first_delta: 0,
+            previous_delta: 0,
             bit_widths: vec![0; mini_blocks],
             total_count: 0,
             min_delta: i64::MAX,
             deltas: vec![0; block_size],
             pos: 0,
             blocks_buffer: Default::default(),
+            mode: Mode::Delta,
+            format: Format::Native,
             block_size,
             mini_blocks,
-            mini_block_size,
+            block_index: false,
+            stats_section: false,
+            checksums: false,
+            versioned: false,
+            block: None,
+            block_stats: vec![],
+            stats: None,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        assert_eq!(
+            self.total_count, 0,
+            "Mode must be set before writing values"
+        );
+        self.mode = mode;
+        self
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn with_format(mut self, format: Format) -> Self {
+        assert_eq!(
+            self.total_count, 0,
+            "Format must be set before writing values"
+        );
+        self.format = format;
+        self
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Prefixes every block with its min, max and last value, which lets
+    /// [`Decoder::filter_range`] skip blocks without unpacking them. Only supported by
+    /// [`Format::Native`].
+    pub fn with_block_index(mut self, block_index: bool) -> Self {
+        assert_eq!(
+            self.total_count, 0,
+            "Block index must be set before writing values"
+        );
+        self.block_index = block_index;
+        self
+    }
+
+    /// Writes the [`Stats`] of the stream and of every block after the header. Only
+    /// supported by [`Format::Native`].
+    pub fn with_stats(mut self, stats: bool) -> Self {
+        assert_eq!(
+            self.total_count, 0,
+            "Stats must be set before writing values"
+        );
+        self.stats_section = stats;
+        self
+    }
+
+    /// Follows the header, and the stats section if any, with a CRC32C of them, and
+    /// precedes every block with a CRC32C of the block, so [`Decoder`] can detect
+    /// corrupted bytes. Only supported by [`Format::Native`].
+    pub fn with_checksums(mut self, checksums: bool) -> Self {
+        assert_eq!(
+            self.total_count, 0,
+            "Checksums must be set before writing values"
+        );
+        self.checksums = checksums;
+        self
+    }
+
+    /// Starts the stream with a magic number and a format version, and stores the stream
+    /// options in a field of their own, so later layout changes can be told apart from
+    /// this one. Decoders read both versioned and unversioned streams. Only supported by
+    /// [`Format::Native`].
+    pub fn with_versioned_header(mut self, versioned: bool) -> Self {
+        assert_eq!(
+            self.total_count, 0,
+            "Versioned header must be set before writing values"
+        );
+        self.versioned = versioned;
+        self
+    }
+
+    /// Stats of every value written so far.
+    pub fn stats(&self) -> Option<Stats> {
+        self.stats
+    }
+
+    /// Stats of every flushed block, kept only [`with_stats`](Encoder::with_stats). The
+    /// first block also covers the values stored in the header.
+    pub fn block_stats(&self) -> &[Stats] {
+        &self.block_stats
+    }
+
+    fn update_stats(&mut self, value: i64) {
+        let previous = self.previous_value;
+        let push = |stats: &mut Option<Stats>| match stats {
+            Some(stats) => stats.push(value, previous),
+            None => *stats = Some(Stats::new(value)),
+        };
+        push(&mut self.stats);
+        // Block stats are only needed for the block index and the stats section.
+        if self.block_index || self.stats_section {
+            push(&mut self.block);
         }
     }
 
     pub fn write_integer(&mut self, value: i64) -> anyhow::Result<()> {
+        self.update_stats(value);
         self.total_count += 1;
 
         if self.total_count == 1 {
@@ -63,9 +316,21 @@ impl Encoder {
             return Ok(());
         }
 
-        let delta = value - self.previous_value;
+        let delta = value.wrapping_sub(self.previous_value);
         self.previous_value = value;
 
+        let delta = match self.mode {
+            Mode::Delta => delta,
+            Mode::DeltaOfDelta => {
+                let previous_delta = std::mem::replace(&mut self.previous_delta, delta);
+                if self.total_count == 2 {
+                    self.first_delta = delta;
+                    return Ok(());
+                }
+                delta.wrapping_sub(previous_delta)
+            }
+        };
+
         self.deltas[self.pos] = delta;
         self.pos += 1;
 
@@ -81,13 +346,12 @@ impl Encoder {
         if self.pos == 0 {
             return Ok(());
         }
-        let extra_to_write = self.block_size - self.pos;
-        for _ in 0..extra_to_write {
-            self.write_integer(self.previous_value.wrapping_add(self.min_delta))?;
-        }
-        self.total_count -= extra_to_write;
+        // Padding with `min_delta` keeps the bit widths of the last block unchanged.
+        let min_delta = self.min_delta;
+        self.deltas[self.pos..].fill(min_delta);
+        self.pos = self.block_size;
 
-        Ok(())
+        self.flush_buffer()
     }
 
     fn flush_buffer(&mut self) -> anyhow::Result<()> {
@@ -95,129 +359,357 @@ impl Encoder {
             return Ok(());
         }
 
-        (0..self.pos).for_each(|i| {
-            let new_delta = self.deltas.get(i).unwrap().wrapping_sub(self.min_delta);
-            self.deltas[i] = new_delta;
-        });
-
-        self.write_min_delta()?;
-
-        let mini_blocks_to_flush = self.mini_block_count_to_flush(self.pos);
-        self.calculate_bit_widths_for_delta_block_buffer(mini_blocks_to_flush);
-        let _ = self
-            .blocks_buffer
-            .write(&self.bit_widths[0..mini_blocks_to_flush]);
+        let deltas: Vec<u64> = self.deltas[..self.pos]
+            .iter()
+            .map(|delta| delta.wrapping_sub(self.min_delta) as u64)
+            .collect();
 
-        (0..mini_blocks_to_flush).for_each(|index| {
-            let bit_width = std::mem::take(&mut self.bit_widths[index]);
-            if bit_width > 0 {
-                let start = self.mini_block_size * index;
-                let end = self.mini_block_size * (index + 1);
+        let block = self.block.take();
+        if self.stats_section {
+            self.block_stats
+                .push(block.expect("Flushed block without stats"));
+        }
 
-                let deltas: Vec<u64> = self.deltas[start..end].iter().map(|i| *i as u64).collect();
+        let start = self.blocks_buffer.get_ref().len();
+        if self.checksums {
+            write_checksum(&mut self.blocks_buffer, 0)?;
+        }
 
-                let mut compressed = vec![0u8; BLOCK_LEN / 8 * bit_width as usize];
-                bitpacker64::pack(&deltas, &mut compressed, bit_width);
-                self.blocks_buffer.write(&compressed).unwrap();
+        if self.block_index {
+            let block = block.expect("Flushed block without stats");
+            let mut mini_blocks = vec![];
+            self.bit_widths = write_mini_blocks(&mut mini_blocks, &deltas)?;
+            BlockIndex {
+                packed_len: mini_blocks.len() - self.mini_blocks,
+                min: block.min,
+                max: block.max,
+                last_value: self.previous_value,
+                last_delta: self.previous_delta,
             }
-        });
+            .write(&mut self.blocks_buffer, self.mode)?;
+            write_zig_zag_var_int(&mut self.blocks_buffer, self.format, self.min_delta)?;
+            self.blocks_buffer
+                .write_all(&mini_blocks)
+                .map_err(|e| anyhow::anyhow!(e))?;
+        } else {
+            write_zig_zag_var_int(&mut self.blocks_buffer, self.format, self.min_delta)?;
+            self.bit_widths = write_mini_blocks(&mut self.blocks_buffer, &deltas)?;
+        }
+
+        if self.checksums {
+            let block = &mut self.blocks_buffer.get_mut()[start..];
+            let checksum = crc32c::crc32c(&block[4..]);
+            block[..4].copy_from_slice(&checksum.to_le_bytes());
+        }
 
         self.min_delta = i64::MAX;
         self.pos = 0;
         Ok(())
     }
 
-    fn write_min_delta(&mut self) -> anyhow::Result<()> {
-        write_zig_zag_var_int(&mut self.blocks_buffer, self.min_delta)
+    /// Flushes any pending values, writes the stream and resets the encoder for the next
+    /// one. The encoder is reset even if writing fails, so a broken stream can't leak into
+    /// the next.
+    pub fn write<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        let result = self.write_stream(io);
+        self.reset();
+        result
     }
 
-    fn mini_block_count_to_flush(&self, number_count: usize) -> usize {
-        ((number_count as f64) / (self.mini_block_size as f64)).ceil() as usize
+    fn write_stream<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        if self.format == Format::Parquet && self.mode != Mode::Delta {
+            anyhow::bail!("Parquet format does not support {:?}", self.mode)
+        }
+        self.flush()?;
+
+        let mut header = vec![];
+        self.header().write(&mut header, self.format)?;
+        if self.mode == Mode::DeltaOfDelta {
+            write_zig_zag_var_int(&mut header, self.format, self.first_delta)?;
+        }
+        if self.stats_section {
+            self.write_stats(&mut header)?;
+        }
+        if self.checksums {
+            let checksum = crc32c::crc32c(&header);
+            write_checksum(&mut header, checksum)?;
+        }
+
+        io.write_all(&header).map_err(|e| anyhow::anyhow!(e))?;
+        io.write_all(self.blocks_buffer.get_ref())
+            .map_err(|e| anyhow::anyhow!(e))
     }
 
-    fn calculate_bit_widths_for_delta_block_buffer(&mut self, mini_blocks_to_flush: usize) {
-        (0..mini_blocks_to_flush).for_each(|index| {
-            let deltas: Vec<u64> = self.deltas
-                [self.mini_block_size * index..self.mini_block_size * (index + 1)]
-                .iter()
-                .map(|i| *i as u64)
-                .collect();
-            let bit_width = num_bits(&deltas);
-            self.bit_widths[index] = bit_width;
-        })
+    pub fn write_to_buf<B: BufMut>(&mut self, buf: &mut B) -> anyhow::Result<()> {
+        self.write(&mut buf.writer())
     }
 
-    pub fn write<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
-        io.write_vlq(self.block_size as i64)
-            .map_err(|e| anyhow::anyhow!("Block size failed {}, {:?}", self.block_size, e))?;
-        io.write_vlq(self.mini_blocks as i64)
-            .map_err(|e| anyhow::anyhow!("Mini block size failed {}, {:?}", self.mini_blocks, e))?;
-        io.write_vlq(self.total_count as i64)
-            .map_err(|e| anyhow::anyhow!("Total size failed {}, {:?}", self.total_count, e))?;
+    /// Like [`Encoder::write`], but into a new buffer.
+    pub fn finish(&mut self) -> anyhow::Result<Bytes> {
+        let mut buf = BytesMut::with_capacity(self.blocks_buffer.get_ref().len() + 32);
+        self.write_to_buf(&mut buf)?;
+        Ok(buf.freeze())
+    }
 
-        write_zig_zag_var_int(io, self.first_value)?;
+    /// Clears all written values, keeping the options and the allocated buffers.
+    pub fn reset(&mut self) {
+        self.first_value = 0;
+        self.previous_value = 0;
+        self.first_delta = 0;
+        self.previous_delta = 0;
+        self.bit_widths.fill(0);
+        self.total_count = 0;
+        self.min_delta = i64::MAX;
+        self.pos = 0;
+        self.blocks_buffer.get_mut().clear();
+        self.blocks_buffer.set_position(0);
+        self.block = None;
+        self.block_stats.clear();
+        self.stats = None;
+    }
 
-        let bytes = std::mem::take(&mut self.blocks_buffer).into_inner();
-        io.write_all(&bytes).map_err(|e| anyhow::anyhow!(e))?;
+    /// The stream stats, if any values were written, and the number of blocks followed by
+    /// the stats of each.
+    fn write_stats<T: Write>(&self, io: &mut T) -> anyhow::Result<()> {
+        if let Some(stats) = self.stats {
+            stats.write(io)?;
+        }
+        write_var_int(io, Format::Native, self.block_stats.len() as u64)?;
+        for stats in &self.block_stats {
+            stats.write(io)?;
+        }
         Ok(())
     }
+
+    fn header(&self) -> Header {
+        Header {
+            block_size: self.block_size,
+            mini_blocks: self.mini_blocks,
+            total_count: self.total_count,
+            flags: self.mode.header_bits() | self.header_flags(),
+            first_value: self.first_value,
+            version: self.versioned.then_some(HEADER_VERSION),
+        }
+    }
+
+    fn header_flags(&self) -> i64 {
+        let mut flags = 0;
+        if self.block_index {
+            flags |= BLOCK_INDEX_FLAG;
+        }
+        if self.stats_section {
+            flags |= STATS_FLAG;
+        }
+        if self.checksums {
+            flags |= CHECKSUM_FLAG;
+        }
+        flags
+    }
 }
 
+/// Decodes a stream from any [`Read`], copying each packed mini block before unpacking it.
+pub type Decoder<T> = BlockDecoder<ReadInput<T>>;
+/// Decodes a stream borrowed from a slice, unpacking mini blocks in place.
+pub type SliceDecoder<'a> = BlockDecoder<&'a [u8]>;
+/// Decodes a stream held in [`Bytes`], unpacking mini blocks in place.
+pub type BytesDecoder = BlockDecoder<BytesInput>;
+
 #[derive(Debug)]
-pub struct Decoder<T: Read> {
+pub struct BlockDecoder<I: Input> {
     pub first_value: i64,
     pub previous_value: i64,
+    pub first_delta: i64,
+    pub previous_delta: i64,
     pub bit_widths: VecDeque<u8>,
     pub total_count: usize,
     pub min_delta: i64,
     pub deltas: VecDeque<i64>,
     pub values_read: usize,
-    pub io: T,
+    pub io: I,
 
+    mode: Mode,
+    format: Format,
+    block_size: usize,
     mini_blocks: usize,
     mini_block_size: usize,
+    unpacked: Vec<u64>,
+    /// Verified packed mini blocks of the current block, when checksums are verified.
+    packed: Vec<u8>,
+    packed_pos: usize,
+    has_block_index: bool,
+    block_index: Option<BlockIndex>,
+    has_checksums: bool,
+    verify_checksums: bool,
+    blocks_read: usize,
+    version: Option<u8>,
+    stats: Option<Stats>,
+    block_stats: Vec<Stats>,
 }
 
 impl<T: Read> Decoder<T> {
-    pub fn new(mut io: T) -> anyhow::Result<Self> {
-        let block_size: i64 = io.read_vlq().map_err(|e| anyhow::anyhow!(e))?;
-        let mini_blocks: i64 = io.read_vlq().map_err(|e| anyhow::anyhow!(e))?;
-        let total_count: i64 = io.read_vlq().map_err(|e| anyhow::anyhow!(e))?;
+    pub fn new(io: T) -> anyhow::Result<Self> {
+        Self::with_format(io, Format::Native)
+    }
+
+    pub fn with_format(io: T, format: Format) -> anyhow::Result<Self> {
+        Self::from_input(ReadInput::new(io), format)
+    }
+}
 
-        if block_size == 0 || mini_blocks == 0 || (block_size / mini_blocks) as usize != BLOCK_LEN {
-            anyhow::bail!("Invalid header {} {}", block_size, mini_blocks)
+impl<'a> SliceDecoder<'a> {
+    pub fn new(data: &'a [u8]) -> anyhow::Result<Self> {
+        Self::with_format(data, Format::Native)
+    }
+
+    pub fn with_format(data: &'a [u8], format: Format) -> anyhow::Result<Self> {
+        Self::from_input(data, format)
+    }
+}
+
+impl BytesDecoder {
+    pub fn new(bytes: Bytes) -> anyhow::Result<Self> {
+        Self::with_format(bytes, Format::Native)
+    }
+
+    pub fn with_format(bytes: Bytes, format: Format) -> anyhow::Result<Self> {
+        Self::from_input(BytesInput::new(bytes), format)
+    }
+}
+
+impl<I: Input> BlockDecoder<I> {
+    pub fn from_input(io: I, format: Format) -> anyhow::Result<Self> {
+        Self::open(io, format, true)
+    }
+
+    /// Like [`BlockDecoder::from_input`], but doesn't verify the checksums of the stream,
+    /// for input that is already trusted.
+    pub fn from_input_unverified(io: I, format: Format) -> anyhow::Result<Self> {
+        Self::open(io, format, false)
+    }
+
+    fn open(mut io: I, format: Format, verify_checksums: bool) -> anyhow::Result<Self> {
+        let mut checksummed = ChecksumReader::new(&mut io, verify_checksums);
+        let header = Header::read(&mut checksummed, format)?;
+        if header.flags & !KNOWN_FLAGS != 0 {
+            anyhow::bail!("Unknown header flags {:#b}", header.flags & !KNOWN_FLAGS)
+        }
+        let mode = Mode::from_header_bits(header.flags)?;
+
+        let first_value = header.first_value;
+        let first_delta = match mode {
+            Mode::Delta => 0,
+            Mode::DeltaOfDelta => decode_zig_zag_var_int(&mut checksummed, format)?,
+        };
+
+        let (stats, block_stats) = if header.flags & STATS_FLAG != 0 {
+            read_stats(&mut checksummed, &header, mode)?
+        } else {
+            (None, vec![])
+        };
+
+        let actual = checksummed.checksum;
+        let has_checksums = header.flags & CHECKSUM_FLAG != 0;
+        if has_checksums {
+            let expected = read_checksum(&mut io)?;
+            if verify_checksums && expected != actual {
+                return Err(ChecksumMismatch {
+                    block: None,
+                    expected,
+                    actual,
+                }
+                .into());
+            }
         }
 
-        let first_value = decode_zig_zag_var_int(&mut io)?;
+        // Every block holds at least its min delta and bit widths, so in memory input can
+        // rule out counts it is too short for before anything is allocated.
+        let blocks = header
+            .total_count
+            .saturating_sub(mode.header_values())
+            .div_ceil(header.block_size);
+        if let Some(remaining) = io.remaining_len() {
+            if blocks.saturating_mul(1 + header.mini_blocks) > remaining {
+                anyhow::bail!(
+                    "Header declares {} values, only {} bytes left",
+                    header.total_count,
+                    remaining
+                )
+            }
+        }
 
         let mut decoder = Self {
-            total_count: total_count as usize,
+            total_count: header.total_count,
             first_value,
             previous_value: first_value,
-            bit_widths: VecDeque::with_capacity(mini_blocks as usize),
+            first_delta,
+            previous_delta: first_delta,
+            bit_widths: Default::default(),
             min_delta: 0,
             deltas: Default::default(),
             values_read: 0,
             io,
-            mini_blocks: mini_blocks as usize,
-            mini_block_size: BLOCK_LEN,
+            mode,
+            format,
+            block_size: header.block_size,
+            mini_blocks: header.mini_blocks,
+            mini_block_size: header.mini_block_size(),
+            unpacked: vec![],
+            packed: vec![],
+            packed_pos: 0,
+            has_block_index: header.flags & BLOCK_INDEX_FLAG != 0,
+            block_index: None,
+            has_checksums,
+            verify_checksums,
+            blocks_read: 0,
+            version: header.version,
+            stats,
+            block_stats,
         };
 
-        if total_count > 1 {
+        if decoder.total_count > mode.header_values() {
             decoder.read_block()?;
         }
 
         Ok(decoder)
     }
 
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Version of the stream's header, or `None` if it is unversioned.
+    pub fn version(&self) -> Option<u8> {
+        self.version
+    }
+
+    /// Stats of the whole stream, if it was written with them and isn't empty.
+    pub fn stats(&self) -> Option<Stats> {
+        self.stats
+    }
+
+    /// Stats of every block, if the stream was written with them.
+    pub fn block_stats(&self) -> &[Stats] {
+        &self.block_stats
+    }
+
     pub fn read_integers(&mut self) -> anyhow::Result<Vec<i64>> {
-        let mut values = vec![0; self.total_count];
+        // The count comes from the header, so values are pushed as they are read.
+        let mut values = vec![];
+        while !self.all_read() {
+            values.push(self.read_integer()?);
+        }
+        Ok(values)
+    }
+
+    /// Fills `values` with the next values and returns how many were read.
+    pub fn read_into(&mut self, values: &mut [i64]) -> anyhow::Result<usize> {
+        let count = values.len().min(self.total_count - self.values_read);
 
-        for i in 0..self.total_count {
-            values[i] = self.read_integer()?
+        for value in values[..count].iter_mut() {
+            *value = self.read_integer()?
         }
 
-        Ok(values)
+        Ok(count)
     }
 
     pub fn read_integer(&mut self) -> anyhow::Result<i64> {
@@ -228,15 +720,28 @@ impl<T: Read> Decoder<T> {
             return Ok(self.first_value);
         }
 
-        if self.deltas.len() == 0 {
-            self.read_deltas()?;
-        }
+        let delta = match self.mode {
+            Mode::Delta => self.next_delta()?,
+            Mode::DeltaOfDelta if self.values_read == 2 => self.first_delta,
+            Mode::DeltaOfDelta => {
+                let delta = self.previous_delta.wrapping_add(self.next_delta()?);
+                self.previous_delta = delta;
+                delta
+            }
+        };
 
-        let value = self.previous_value + self.deltas.pop_front().unwrap_or_default();
+        let value = self.previous_value.wrapping_add(delta);
         self.previous_value = value;
         Ok(value)
     }
 
+    fn next_delta(&mut self) -> anyhow::Result<i64> {
+        if self.deltas.is_empty() {
+            self.read_deltas()?;
+        }
+        Ok(self.deltas.pop_front().unwrap_or_default())
+    }
+
     pub fn check_read(&self) {
         if self.all_read() {
             panic!("All values read");
@@ -247,36 +752,160 @@ impl<T: Read> Decoder<T> {
         self.values_read == self.total_count
     }
 
+    /// Returns the indices of the values within `range`. Blocks whose min and max rule
+    /// them out, or put them entirely within `range`, are skipped without unpacking when
+    /// the stream has a block index; otherwise every value is decoded and tested.
+    pub fn filter_range<R: RangeBounds<i64>>(&mut self, range: R) -> anyhow::Result<Vec<usize>> {
+        if self.values_read != 0 {
+            anyhow::bail!("Filtering must start at the first value")
+        }
+
+        let mut indices = vec![];
+        let header_values = self.mode.header_values().min(self.total_count);
+        while self.values_read < header_values {
+            let index = self.values_read;
+            if range.contains(&self.read_integer()?) {
+                indices.push(index);
+            }
+        }
+
+        while !self.all_read() {
+            if self.bit_widths.is_empty() {
+                self.read_block()?;
+            }
+            let start = self.values_read;
+            let count = self.block_size.min(self.total_count - start);
+
+            match self.block_index.take() {
+                Some(block_index) if !block_index.overlaps(&range) => {
+                    self.skip_block(block_index, count)?
+                }
+                Some(block_index) if block_index.within(&range) => {
+                    indices.extend(start..start + count);
+                    self.skip_block(block_index, count)?
+                }
+                _ => {
+                    for index in start..start + count {
+                        if range.contains(&self.read_integer()?) {
+                            indices.push(index);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(indices)
+    }
+
+    fn skip_block(&mut self, block_index: BlockIndex, count: usize) -> anyhow::Result<()> {
+        // Verified blocks have already been read whole.
+        if !self.buffers_blocks() {
+            self.io.take_bytes(block_index.packed_len)?;
+        }
+
+        self.bit_widths.clear();
+        self.deltas.clear();
+        self.previous_value = block_index.last_value;
+        self.previous_delta = block_index.last_delta;
+        self.values_read += count;
+        Ok(())
+    }
+
+    /// Whether blocks are read whole to verify their checksum, and their mini blocks
+    /// unpacked from `packed` rather than `io`.
+    fn buffers_blocks(&self) -> bool {
+        self.has_checksums && self.verify_checksums
+    }
+
     pub fn read_block(&mut self) -> anyhow::Result<()> {
-        self.min_delta = decode_zig_zag_var_int(&mut self.io)?;
+        if self.buffers_blocks() {
+            return self.read_checksummed_block();
+        }
+        if self.has_checksums {
+            read_checksum(&mut self.io)?;
+        }
+        if self.has_block_index {
+            self.block_index = Some(BlockIndex::read(&mut self.io, self.mode)?);
+        }
+        self.min_delta = decode_zig_zag_var_int(&mut self.io, self.format)?;
 
-        let mut bit_widths = vec![0; self.mini_blocks];
-        self.io
-            .read(&mut bit_widths)
-            .map_err(|e| anyhow::anyhow!(e))?;
+        let bit_widths = self.io.take_bytes(self.mini_blocks)?;
+        check_bit_widths(bit_widths)?;
+        if let Some(block_index) = &self.block_index {
+            let len: usize = bit_widths
+                .iter()
+                .map(|bit_width| packed_len(self.mini_block_size, *bit_width))
+                .sum();
+            if block_index.packed_len != len {
+                anyhow::bail!(
+                    "Block index covers {} bytes, block has {}",
+                    block_index.packed_len,
+                    len
+                )
+            }
+        }
         self.bit_widths.extend(bit_widths.iter());
         Ok(())
     }
 
+    /// Reads a whole block and verifies its checksum, keeping its packed mini blocks to be
+    /// unpacked as they are read, so no value of a corrupted block is returned.
+    fn read_checksummed_block(&mut self) -> anyhow::Result<()> {
+        let expected = read_checksum(&mut self.io)?;
+
+        let mut checksummed = ChecksumReader::new(&mut self.io, true);
+        if self.has_block_index {
+            self.block_index = Some(BlockIndex::read(&mut checksummed, self.mode)?);
+        }
+        self.min_delta = decode_zig_zag_var_int(&mut checksummed, self.format)?;
+        let mut actual = checksummed.checksum;
+
+        let bit_widths = self.io.take_bytes(self.mini_blocks)?.to_vec();
+        check_bit_widths(&bit_widths)?;
+        actual = crc32c::crc32c_append(actual, &bit_widths);
+
+        let len = bit_widths
+            .iter()
+            .map(|bit_width| packed_len(self.mini_block_size, *bit_width))
+            .sum();
+        let packed = self.io.take_bytes(len)?;
+        actual = crc32c::crc32c_append(actual, packed);
+        if expected != actual {
+            return Err(ChecksumMismatch {
+                block: Some(self.blocks_read),
+                expected,
+                actual,
+            }
+            .into());
+        }
+        self.blocks_read += 1;
+
+        self.packed.clear();
+        self.packed.extend_from_slice(packed);
+        self.packed_pos = 0;
+        self.bit_widths.extend(bit_widths);
+        Ok(())
+    }
+
     pub fn read_deltas(&mut self) -> anyhow::Result<()> {
-        if self.bit_widths.len() == 0 {
+        if self.bit_widths.is_empty() {
             self.read_block()?;
         }
 
         let bit_width = self.bit_widths.pop_front().unwrap_or_default();
-        let result = if bit_width > 0u8 {
-            let mut packed = vec![0_u8; BLOCK_LEN / 8 * bit_width as usize];
-            self.io.read(&mut packed).map_err(|e| anyhow::anyhow!(e))?;
-            let mut result = vec![0_u64; self.mini_block_size];
-
-            bitpacker64::unpack(&packed, &mut result, bit_width);
-            result
+        let len = packed_len(self.mini_block_size, bit_width);
+        self.unpacked.resize(self.mini_block_size, 0);
+        if self.buffers_blocks() {
+            let packed = &self.packed[self.packed_pos..self.packed_pos + len];
+            unpack_mini_block(packed, bit_width, &mut self.unpacked);
+            self.packed_pos += len;
         } else {
-            vec![0; self.mini_block_size]
-        };
+            let packed = self.io.take_bytes(len)?;
+            unpack_mini_block(packed, bit_width, &mut self.unpacked);
+        }
 
         self.deltas.extend(
-            result
+            self.unpacked
                 .iter()
                 .map(|i| (*i as i64).wrapping_add(self.min_delta)),
         );
@@ -284,3 +913,35 @@ impl<T: Read> Decoder<T> {
         Ok(())
     }
 }
+
+/// Reads the stats section, checking it agrees with the counts in `header`.
+fn read_stats<T: Read>(
+    io: &mut T,
+    header: &Header,
+    mode: Mode,
+) -> anyhow::Result<(Option<Stats>, Vec<Stats>)> {
+    let mut stats = None;
+    if header.total_count > 0 {
+        let stream = Stats::read(io)?;
+        if stream.count != header.total_count {
+            anyhow::bail!(
+                "Stats for {} values, stream has {}",
+                stream.count,
+                header.total_count
+            )
+        }
+        stats = Some(stream);
+    }
+    let blocks = read_var_int(io, Format::Native)? as usize;
+    let expected = header
+        .total_count
+        .saturating_sub(mode.header_values())
+        .div_ceil(header.block_size);
+    if blocks != expected {
+        anyhow::bail!("Stats for {} blocks, stream has {}", blocks, expected)
+    }
+    let block_stats = (0..blocks)
+        .map(|_| Stats::read(io))
+        .collect::<anyhow::Result<_>>()?;
+    Ok((stats, block_stats))
+}
@@ -0,0 +1,109 @@
+//! Conversions between delta streams and Arrow arrays of integers.
+//!
+//! Values are decoded straight into the array's value buffer, and the validity bitmap of a
+//! [`NullableDecoder`] already has Arrow's layout, so it becomes the null buffer as is.
+
+use crate::delta_encoding_64::{
+    BlockDecoder, DeltaVec, Encoder, Input, NullableDecoder, NullableEncoder,
+};
+use arrow_array::types::Int64Type;
+use arrow_array::{Array, ArrowPrimitiveType, Int64Array, PrimitiveArray};
+use arrow_buffer::{BooleanBuffer, Buffer, NullBuffer};
+use std::io::Read;
+
+fn from_i64<T: ArrowPrimitiveType>(value: i64) -> anyhow::Result<T::Native>
+where
+    T::Native: TryFrom<i64>,
+{
+    T::Native::try_from(value)
+        .map_err(|_| anyhow::anyhow!("{} out of range for {}", value, T::DATA_TYPE))
+}
+
+impl Encoder {
+    /// Writes every value of `array`, which must not contain nulls; use
+    /// [`NullableEncoder::write_array`] for arrays that do.
+    pub fn write_array<T>(&mut self, array: &PrimitiveArray<T>) -> anyhow::Result<()>
+    where
+        T: ArrowPrimitiveType,
+        T::Native: Into<i64>,
+    {
+        if array.null_count() > 0 {
+            anyhow::bail!("Array has {} nulls", array.null_count())
+        }
+        for value in array.values() {
+            self.write_integer((*value).into())?;
+        }
+        Ok(())
+    }
+}
+
+impl<I: Input> BlockDecoder<I> {
+    /// Decodes the remaining values into an array without nulls.
+    pub fn read_array<T>(&mut self) -> anyhow::Result<PrimitiveArray<T>>
+    where
+        T: ArrowPrimitiveType,
+        T::Native: TryFrom<i64>,
+    {
+        let mut values = Vec::with_capacity(self.total_count - self.values_read);
+        while !self.all_read() {
+            values.push(from_i64::<T>(self.read_integer()?)?);
+        }
+        Ok(PrimitiveArray::new(values.into(), None))
+    }
+}
+
+impl NullableEncoder {
+    pub fn write_array<T>(&mut self, array: &PrimitiveArray<T>) -> anyhow::Result<()>
+    where
+        T: ArrowPrimitiveType,
+        T::Native: Into<i64>,
+    {
+        for value in array.iter() {
+            self.write_integer(value.map(Into::into))?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> NullableDecoder<R> {
+    /// Decodes the remaining values into an array, with the validity bitmap as its nulls.
+    pub fn read_array<T>(&mut self) -> anyhow::Result<PrimitiveArray<T>>
+    where
+        T: ArrowPrimitiveType,
+        T::Native: TryFrom<i64>,
+    {
+        let offset = self.values_read;
+        let len = self.total_count - offset;
+        let nulls = NullBuffer::new(BooleanBuffer::new(
+            Buffer::from(self.validity.as_slice()),
+            offset,
+            len,
+        ));
+
+        let mut values = Vec::with_capacity(len);
+        while !self.all_read() {
+            let value = self.read_integer()?.unwrap_or_default();
+            values.push(from_i64::<T>(value)?);
+        }
+        Ok(PrimitiveArray::new(values.into(), Some(nulls)))
+    }
+}
+
+impl DeltaVec {
+    /// Builds a `DeltaVec` from an array without nulls.
+    pub fn from_array(array: &Int64Array) -> anyhow::Result<Self> {
+        if array.null_count() > 0 {
+            anyhow::bail!("Array has {} nulls", array.null_count())
+        }
+        let mut delta_vec = Self::new();
+        delta_vec.extend(array.values().to_vec());
+        Ok(delta_vec)
+    }
+
+    pub fn to_array(&self) -> anyhow::Result<Int64Array> {
+        match self.decoder() {
+            Some(mut decoder) => decoder.read_array::<Int64Type>(),
+            None => Ok(Int64Array::from(Vec::<i64>::new())),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Decoding from [`AsyncRead`] and encoding to [`AsyncWrite`].
+//!
+//! [`AsyncDecoder`] runs the regular [`BlockDecoder`] over the bytes received so far.
+//! Before every value it makes sure the next block header and mini block are buffered:
+//! when the decoder runs out of input, its read is rewound and retried once more bytes
+//! have arrived, so the async side never parses the stream itself.
+
+use crate::delta_encoding_64::{packed_len, BlockDecoder, Encoder, Format, Input};
+use std::io::{ErrorKind, Read};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Bytes requested from the reader at least, doubled while a read keeps running out.
+const MIN_READ_LEN: usize = 4096;
+
+/// The bytes received so far, of which the first `pos` have been decoded.
+#[derive(Debug, Default)]
+pub struct BufferInput {
+    buffer: Vec<u8>,
+    pos: usize,
+}
+
+impl BufferInput {
+    fn remaining(&self) -> usize {
+        self.buffer.len() - self.pos
+    }
+
+    /// Appends up to `len` bytes from `io`, returning how many arrived.
+    async fn fill<R: AsyncRead + Unpin>(
+        &mut self,
+        io: &mut R,
+        len: usize,
+    ) -> anyhow::Result<usize> {
+        if self.pos > self.buffer.len() / 2 {
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+        }
+        let start = self.buffer.len();
+        self.buffer.resize(start + len, 0);
+        let read = io
+            .read(&mut self.buffer[start..])
+            .await
+            .map_err(|e| anyhow::anyhow!(e));
+        self.buffer.truncate(start + *read.as_ref().unwrap_or(&0));
+        read
+    }
+}
+
+impl Read for BufferInput {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = buf.len().min(self.remaining());
+        buf[..read].copy_from_slice(&self.buffer[self.pos..self.pos + read]);
+        self.pos += read;
+        Ok(read)
+    }
+}
+
+impl Input for BufferInput {
+    fn take_bytes(&mut self, len: usize) -> anyhow::Result<&[u8]> {
+        if len > self.remaining() {
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+        let start = self.pos;
+        self.pos += len;
+        Ok(&self.buffer[start..self.pos])
+    }
+}
+
+fn is_eof(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|error| error.kind() == ErrorKind::UnexpectedEof)
+}
+
+async fn read_more<R: AsyncRead + Unpin>(
+    io: &mut R,
+    input: &mut BufferInput,
+    len: usize,
+) -> anyhow::Result<()> {
+    if input.fill(io, len.max(MIN_READ_LEN)).await? == 0 {
+        anyhow::bail!("Stream truncated after {} bytes", input.buffer.len())
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct AsyncDecoder<R: AsyncRead + Unpin> {
+    pub decoder: BlockDecoder<BufferInput>,
+    pub io: R,
+}
+
+impl<R: AsyncRead + Unpin> AsyncDecoder<R> {
+    pub async fn new(io: R) -> anyhow::Result<Self> {
+        Self::with_format(io, Format::Native).await
+    }
+
+    pub async fn with_format(mut io: R, format: Format) -> anyhow::Result<Self> {
+        let mut received = BufferInput::default();
+        loop {
+            let input = BufferInput {
+                buffer: received.buffer.clone(),
+                pos: 0,
+            };
+            match BlockDecoder::from_input(input, format) {
+                Ok(decoder) => return Ok(Self { decoder, io }),
+                Err(e) if is_eof(&e) => {
+                    let len = received.buffer.len();
+                    read_more(&mut io, &mut received, len).await?
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.decoder.total_count
+    }
+
+    pub fn all_read(&self) -> bool {
+        self.decoder.all_read()
+    }
+
+    pub async fn read_integer(&mut self) -> anyhow::Result<i64> {
+        if self.all_read() {
+            anyhow::bail!("All values read")
+        }
+        self.fill_block().await?;
+        self.decoder.read_integer()
+    }
+
+    pub async fn read_integers(&mut self) -> anyhow::Result<Vec<i64>> {
+        // The count comes from the header, so values are pushed as they are read.
+        let mut values = vec![];
+        while !self.all_read() {
+            values.push(self.read_integer().await?);
+        }
+        Ok(values)
+    }
+
+    /// Buffers whatever the next `read_integer` of the decoder consumes.
+    async fn fill_block(&mut self) -> anyhow::Result<()> {
+        let decoder = &mut self.decoder;
+        if decoder.values_read < decoder.mode.header_values() || !decoder.deltas.is_empty() {
+            return Ok(());
+        }
+
+        while decoder.bit_widths.is_empty() {
+            let pos = decoder.io.pos;
+            match decoder.read_block() {
+                Ok(()) => {}
+                Err(e) if is_eof(&e) => {
+                    decoder.io.pos = pos;
+                    read_more(&mut self.io, &mut decoder.io, 0).await?
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Verified blocks are read whole, so their mini blocks are already buffered.
+        if decoder.buffers_blocks() {
+            return Ok(());
+        }
+
+        let bit_width = *decoder.bit_widths.front().unwrap();
+        let len = packed_len(decoder.mini_block_size, bit_width);
+        while decoder.io.remaining() < len {
+            let missing = len - decoder.io.remaining();
+            read_more(&mut self.io, &mut decoder.io, missing).await?
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct AsyncEncoder<W: AsyncWrite + Unpin> {
+    pub encoder: Encoder,
+    pub io: W,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncEncoder<W> {
+    pub fn new(encoder: Encoder, io: W) -> Self {
+        Self { encoder, io }
+    }
+
+    pub fn write_integer(&mut self, value: i64) -> anyhow::Result<()> {
+        self.encoder.write_integer(value)
+    }
+
+    /// Writes the stream to `io` and resets the encoder, like [`Encoder::write`].
+    pub async fn write(&mut self) -> anyhow::Result<()> {
+        let bytes = self.encoder.finish()?;
+        self.io
+            .write_all(&bytes)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        self.io.flush().await.map_err(|e| anyhow::anyhow!(e))
+    }
+}
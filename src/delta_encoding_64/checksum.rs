@@ -0,0 +1,68 @@
+//! CRC32C checksums of the parts of a stream.
+
+use std::fmt;
+use std::io::{Read, Write};
+
+/// Returned, wrapped in an [`anyhow::Error`], when a checksum doesn't match the bytes it
+/// covers. `block` is `None` for the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    pub block: Option<usize>,
+    pub expected: u32,
+    pub actual: u32,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.block {
+            Some(block) => write!(f, "Checksum mismatch in block {}", block)?,
+            None => write!(f, "Checksum mismatch in header")?,
+        }
+        write!(
+            f,
+            ", expected {:08x}, got {:08x}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+/// Checksums every byte read through it, unless `enabled` is false.
+pub(crate) struct ChecksumReader<'a, T: Read> {
+    pub io: &'a mut T,
+    pub checksum: u32,
+    pub enabled: bool,
+}
+
+impl<'a, T: Read> ChecksumReader<'a, T> {
+    pub fn new(io: &'a mut T, enabled: bool) -> Self {
+        Self {
+            io,
+            checksum: 0,
+            enabled,
+        }
+    }
+}
+
+impl<T: Read> Read for ChecksumReader<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.io.read(buf)?;
+        if self.enabled {
+            self.checksum = crc32c::crc32c_append(self.checksum, &buf[..read]);
+        }
+        Ok(read)
+    }
+}
+
+pub(crate) fn write_checksum<T: Write>(io: &mut T, checksum: u32) -> anyhow::Result<()> {
+    io.write_all(&checksum.to_le_bytes())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub(crate) fn read_checksum<T: Read>(io: &mut T) -> anyhow::Result<u32> {
+    let mut checksum = [0u8; 4];
+    io.read_exact(&mut checksum)
+        .map_err(|e| anyhow::anyhow!(e))?;
+    Ok(u32::from_le_bytes(checksum))
+}
@@ -1,22 +1,81 @@
-use crate::delta_encoding_64::{Decoder, Encoder};
+use crate::delta_encoding_64::{
+    serde_delta, BlockDecoder, BytesDecoder, BytesInput, Encoder, Input, SliceDecoder,
+};
+use bytes::Bytes;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde_derive::{Deserialize, Serialize};
-use std::io::Cursor;
+use std::hash::{Hash, Hasher};
 use std::ops::Div;
 
+/// Decimals stored as integers scaled by `multiplier`: the power of ten that keeps all
+/// decimal places (up to 8), lowered if needed so that the largest magnitude, `max_value`,
+/// still fits an `i64`. Both are derived from the current values alone and are recomputed
+/// whenever they change.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedDeltaVecDecimal")]
 pub struct DeltaVecDecimal {
+    #[serde(with = "serde_delta::decimal")]
     multiplier: Decimal,
+    #[serde(with = "serde_delta::decimal")]
     max_value: Decimal,
     delta_vec: DeltaVec,
 }
 
+/// A deserialized [`DeltaVecDecimal`] before its multiplier is checked.
+#[derive(Deserialize)]
+struct UncheckedDeltaVecDecimal {
+    #[serde(with = "serde_delta::decimal")]
+    multiplier: Decimal,
+    #[serde(with = "serde_delta::decimal")]
+    max_value: Decimal,
+    delta_vec: DeltaVec,
+}
+
+impl TryFrom<UncheckedDeltaVecDecimal> for DeltaVecDecimal {
+    type Error = anyhow::Error;
+
+    fn try_from(unchecked: UncheckedDeltaVecDecimal) -> anyhow::Result<Self> {
+        let UncheckedDeltaVecDecimal {
+            multiplier,
+            max_value,
+            delta_vec,
+        } = unchecked;
+        if multiplier < Decimal::ONE || !multiplier.fract().is_zero() {
+            anyhow::bail!("Invalid multiplier {}", multiplier)
+        }
+        if delta_vec.is_empty() {
+            return Ok(Self::new());
+        }
+
+        let mut decimals = Self {
+            multiplier,
+            max_value,
+            delta_vec,
+        };
+        // Older versions stored the largest signed value, so check it against the values.
+        decimals.max_value = decimals
+            .to_vec()
+            .iter()
+            .map(Decimal::abs)
+            .max()
+            .unwrap_or_default();
+        decimals.check_invariants();
+        Ok(decimals)
+    }
+}
+
+impl Default for DeltaVecDecimal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DeltaVecDecimal {
     pub fn new() -> Self {
         Self {
-            multiplier: Decimal::from(1),
-            max_value: Decimal::MIN,
+            multiplier: Decimal::ONE,
+            max_value: Decimal::ZERO,
             delta_vec: DeltaVec::new(),
         }
     }
@@ -24,12 +83,19 @@ impl DeltaVecDecimal {
         self.delta_vec.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn push(&mut self, item: Decimal) {
         self.extend(vec![item])
     }
 
     pub fn clear(&mut self) {
         self.delta_vec.clear();
+        self.multiplier = Decimal::ONE;
+        self.max_value = Decimal::ZERO;
+        self.check_invariants();
     }
 
     pub fn replace(&mut self, items: Vec<Decimal>) {
@@ -39,10 +105,11 @@ impl DeltaVecDecimal {
 
     pub fn extend(&mut self, items: Vec<Decimal>) {
         let mut current_values = self.to_vec();
-        current_values.extend(items.clone());
+        current_values.extend(items);
         let mut precision = 0;
+        self.max_value = Decimal::ZERO;
         for item in &current_values {
-            self.max_value = self.max_value.max(*item);
+            self.max_value = self.max_value.max(item.abs());
             precision = precision.max(item.normalize().scale())
         }
 
@@ -55,14 +122,16 @@ impl DeltaVecDecimal {
         if self.max_value > Decimal::ONE {
             self.multiplier = self
                 .multiplier
-                .min(Decimal::from(i64::MAX).div(self.max_value).floor());
+                .min(Decimal::from(i64::MAX).div(self.max_value).floor())
+                .max(Decimal::ONE);
         }
 
-        let ints = current_values
+        let ints: Vec<i64> = current_values
             .iter()
             .map(|decimal| (decimal * self.multiplier).to_i64().unwrap_or_default())
             .collect();
         self.delta_vec.replace(ints);
+        self.check_invariants();
     }
 
     pub fn to_vec(&self) -> Vec<Decimal> {
@@ -72,14 +141,76 @@ impl DeltaVecDecimal {
             .map(|int| Decimal::from(int) / self.multiplier)
             .collect()
     }
+
+    /// Checks in debug builds that an empty vector is back in its initial state and the
+    /// multiplier can be divided by.
+    fn check_invariants(&self) {
+        if cfg!(debug_assertions) {
+            assert!(
+                self.multiplier >= Decimal::ONE,
+                "Invalid multiplier {}",
+                self.multiplier
+            );
+            if self.is_empty() {
+                assert_eq!(self.multiplier, Decimal::ONE, "Multiplier not reset");
+                assert_eq!(self.max_value, Decimal::ZERO, "Max value not reset");
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedDeltaVec")]
 pub struct DeltaVec {
+    #[serde(with = "serde_delta::bytes")]
     bytes: Vec<u8>,
     len: usize,
 }
 
+/// A deserialized [`DeltaVec`] before its bytes are checked against its length.
+#[derive(Deserialize)]
+struct UncheckedDeltaVec {
+    #[serde(with = "serde_delta::bytes")]
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl TryFrom<UncheckedDeltaVec> for DeltaVec {
+    type Error = anyhow::Error;
+
+    fn try_from(mut unchecked: UncheckedDeltaVec) -> anyhow::Result<Self> {
+        // `clear` used to keep the length, so vectors serialized after it have no bytes
+        // and a stale length.
+        if unchecked.bytes.is_empty() {
+            return Ok(Self::new());
+        }
+        let values = serde_delta::decode(&unchecked.bytes)?;
+        if values.len() != unchecked.len {
+            anyhow::bail!(
+                "DeltaVec of length {} holds {} values",
+                unchecked.len,
+                values.len()
+            )
+        }
+        // A stream of no values is stored as no bytes.
+        if values.is_empty() {
+            unchecked.bytes.clear();
+        }
+        let delta_vec = Self {
+            bytes: unchecked.bytes,
+            len: unchecked.len,
+        };
+        delta_vec.check_invariants();
+        Ok(delta_vec)
+    }
+}
+
+impl Default for DeltaVec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DeltaVec {
     pub fn new() -> Self {
         Self {
@@ -92,22 +223,27 @@ impl DeltaVec {
         self.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn push(&mut self, item: i64) {
-        let mut encoder = Encoder::new(1);
-        for int in self.to_vec() {
+        let mut encoder = Self::encoder();
+        for int in self.iter() {
             encoder.write_integer(int).unwrap_or_default();
         }
         encoder.write_integer(item).unwrap_or_default();
         self.flush(&mut encoder);
     }
 
-    pub fn extend(&mut self, items: Vec<i64>) {
-        if items.len() == 0 {
+    pub fn extend<T: IntoIterator<Item = i64>>(&mut self, items: T) {
+        let mut items = items.into_iter().peekable();
+        if items.peek().is_none() {
             return;
         }
 
-        let mut encoder = Encoder::new(1);
-        for int in self.to_vec() {
+        let mut encoder = Self::encoder();
+        for int in self.iter() {
             encoder.write_integer(int).unwrap_or_default();
         }
         for item in items {
@@ -118,6 +254,8 @@ impl DeltaVec {
 
     pub fn clear(&mut self) {
         self.bytes.clear();
+        self.len = 0;
+        self.check_invariants();
     }
 
     pub fn replace(&mut self, items: Vec<i64>) {
@@ -125,13 +263,47 @@ impl DeltaVec {
         self.extend(items);
     }
 
+    pub fn first(&self) -> Option<i64> {
+        self.decoder().map(|decoder| decoder.first_value)
+    }
+
+    /// Decodes the whole vector to find the last value.
+    pub fn last(&self) -> Option<i64> {
+        self.iter().last()
+    }
+
+    /// Iterates over the values, decoding them as it goes.
+    pub fn iter(&self) -> Iter<'_> {
+        Values {
+            decoder: self.decoder(),
+        }
+    }
+
     pub fn to_vec(&self) -> Vec<i64> {
-        if self.bytes.len() == 0 {
-            return vec![];
+        match self.decoder() {
+            Some(mut decoder) => decoder.read_integers().unwrap(),
+            None => vec![],
+        }
+    }
+
+    /// The encoded stream, empty if no values were added.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub(crate) fn decoder(&self) -> Option<SliceDecoder<'_>> {
+        if self.bytes.is_empty() {
+            return None;
         }
+        Some(SliceDecoder::new(&self.bytes).unwrap())
+    }
 
-        let buffer = Cursor::new(self.bytes.to_vec());
-        Decoder::new(buffer).unwrap().read_integers().unwrap()
+    /// Persisted bytes are checksummed and versioned, so they can be validated and told
+    /// apart from later layouts.
+    fn encoder() -> Encoder {
+        Encoder::new(1)
+            .with_checksums(true)
+            .with_versioned_header(true)
     }
 
     fn flush(&mut self, encoder: &mut Encoder) {
@@ -140,5 +312,121 @@ impl DeltaVec {
         let mut buffer = std::io::Cursor::new(vec![]);
         encoder.write(&mut buffer).unwrap();
         self.bytes = buffer.into_inner();
+        self.check_invariants();
+    }
+
+    /// Checks in debug builds that `len` is the number of values in `bytes`, and that an
+    /// empty vector holds no bytes at all.
+    fn check_invariants(&self) {
+        if cfg!(debug_assertions) {
+            assert_eq!(
+                self.bytes.is_empty(),
+                self.len == 0,
+                "DeltaVec of length {} holds {} bytes",
+                self.len,
+                self.bytes.len()
+            );
+            let total_count = self
+                .decoder()
+                .map(|decoder| decoder.total_count)
+                .unwrap_or_default();
+            assert_eq!(self.len, total_count, "DeltaVec length out of sync");
+        }
+    }
+}
+
+/// Values decoded one at a time from the stream of a [`DeltaVec`].
+#[derive(Debug)]
+pub struct Values<I: Input> {
+    decoder: Option<BlockDecoder<I>>,
+}
+
+/// Iterator returned by [`DeltaVec::iter`].
+pub type Iter<'a> = Values<&'a [u8]>;
+/// Iterator returned by [`DeltaVec::into_iter`], owning the encoded bytes.
+pub type IntoIter = Values<BytesInput>;
+
+impl<I: Input> Iterator for Values<I> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        let decoder = self
+            .decoder
+            .as_mut()
+            .filter(|decoder| !decoder.all_read())?;
+        Some(
+            decoder
+                .read_integer()
+                .expect("DeltaVec holds a valid stream"),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self
+            .decoder
+            .as_ref()
+            .map(|decoder| decoder.total_count - decoder.values_read)
+            .unwrap_or_default();
+        (len, Some(len))
+    }
+}
+
+impl<I: Input> ExactSizeIterator for Values<I> {}
+
+impl IntoIterator for DeltaVec {
+    type Item = i64;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        let decoder = (!self.bytes.is_empty()).then(|| {
+            BytesDecoder::new(Bytes::from(self.bytes)).expect("DeltaVec holds a valid stream")
+        });
+        Values { decoder }
+    }
+}
+
+impl<'a> IntoIterator for &'a DeltaVec {
+    type Item = i64;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<i64> for DeltaVec {
+    fn from_iter<T: IntoIterator<Item = i64>>(items: T) -> Self {
+        let mut delta_vec = DeltaVec::new();
+        delta_vec.extend(items);
+        delta_vec
+    }
+}
+
+impl Extend<i64> for DeltaVec {
+    fn extend<T: IntoIterator<Item = i64>>(&mut self, items: T) {
+        DeltaVec::extend(self, items)
+    }
+}
+
+impl From<Vec<i64>> for DeltaVec {
+    fn from(items: Vec<i64>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+/// Vectors are equal when they hold the same values, even if those were encoded
+/// differently, e.g. by an older version.
+impl PartialEq for DeltaVec {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && (self.bytes == other.bytes || self.iter().eq(other.iter()))
+    }
+}
+
+impl Eq for DeltaVec {}
+
+impl Hash for DeltaVec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        self.iter().for_each(|value| value.hash(state));
     }
 }
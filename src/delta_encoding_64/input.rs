@@ -0,0 +1,105 @@
+//! Sources a [`BlockDecoder`](super::BlockDecoder) reads from. Slices and `Bytes` hand
+//! out packed mini blocks in place; any other reader copies them into a reused buffer.
+
+use bytes::Bytes;
+use std::io::Read;
+
+pub trait Input: Read {
+    /// Consumes the next `len` bytes.
+    fn take_bytes(&mut self, len: usize) -> anyhow::Result<&[u8]>;
+
+    /// Number of bytes left, if the whole input is already in memory.
+    fn remaining_len(&self) -> Option<usize> {
+        None
+    }
+}
+
+fn check_len(len: usize, remaining: usize) -> anyhow::Result<()> {
+    if len > remaining {
+        anyhow::bail!("Input truncated, {} of {} bytes left", remaining, len)
+    }
+    Ok(())
+}
+
+impl Input for &[u8] {
+    fn take_bytes(&mut self, len: usize) -> anyhow::Result<&[u8]> {
+        check_len(len, self.len())?;
+        let (head, tail) = self.split_at(len);
+        *self = tail;
+        Ok(head)
+    }
+
+    fn remaining_len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+#[derive(Debug)]
+pub struct ReadInput<T: Read> {
+    pub io: T,
+    buffer: Vec<u8>,
+}
+
+impl<T: Read> ReadInput<T> {
+    pub fn new(io: T) -> Self {
+        Self { io, buffer: vec![] }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+}
+
+impl<T: Read> Read for ReadInput<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.io.read(buf)
+    }
+}
+
+impl<T: Read> Input for ReadInput<T> {
+    fn take_bytes(&mut self, len: usize) -> anyhow::Result<&[u8]> {
+        self.buffer.resize(len, 0);
+        self.io
+            .read_exact(&mut self.buffer)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        Ok(&self.buffer)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BytesInput {
+    bytes: Bytes,
+    pos: usize,
+}
+
+impl BytesInput {
+    pub fn new(bytes: Bytes) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// The bytes not consumed yet, sharing the original buffer.
+    pub fn remaining(&self) -> Bytes {
+        self.bytes.slice(self.pos..)
+    }
+}
+
+impl Read for BytesInput {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = (&self.bytes[self.pos..]).read(buf)?;
+        self.pos += read;
+        Ok(read)
+    }
+}
+
+impl Input for BytesInput {
+    fn take_bytes(&mut self, len: usize) -> anyhow::Result<&[u8]> {
+        check_len(len, self.bytes.len() - self.pos)?;
+        let start = self.pos;
+        self.pos += len;
+        Ok(&self.bytes[start..self.pos])
+    }
+
+    fn remaining_len(&self) -> Option<usize> {
+        Some(self.bytes.len() - self.pos)
+    }
+}
@@ -0,0 +1,315 @@
+//! Header and block layout shared by the block based codecs.
+//!
+//! A stream starts with a header of `block_size, mini_blocks, total_count, first_value`.
+//! A versioned header is prefixed with [`HEADER_MAGIC`] and a version byte, and carries the
+//! stream options as a separate `flags` varint before the block size.
+//! Every block is followed by one bit width byte per mini block and the bit packed
+//! mini blocks themselves; mini blocks with a bit width of 0 take no space.
+
+use super::Format;
+use crate::bitpacker64::{num_bits, BLOCK_LEN};
+use crate::{bitpacker64, uleb128, zig_zag};
+use std::io::{Read, Write};
+use vlq::{ReadVlqExt, WriteVlqExt};
+
+/// The block size is always a multiple of `BLOCK_LEN`, so its low bits are free to carry
+/// stream options in the header without breaking streams written before they existed.
+pub(crate) const HEADER_FLAGS_MASK: i64 = BLOCK_LEN as i64 - 1;
+
+/// Starts a versioned header. The first byte alone is a `vlq` varint of 0, which is never
+/// a valid block size, so unversioned streams can't start with it.
+pub(crate) const HEADER_MAGIC: [u8; 4] = [0x80, b'D', b'L', b'T'];
+/// The latest version of the versioned header.
+pub(crate) const HEADER_VERSION: u8 = 1;
+
+/// Parquet requires blocks to be a multiple of 128 values and mini blocks a multiple of 32.
+const PARQUET_BLOCK_MULTIPLE: usize = 128;
+const PARQUET_MINI_BLOCK_MULTIPLE: usize = 32;
+
+/// Largest block a header may declare, so a corrupted header can't request huge buffers.
+const MAX_BLOCK_SIZE: usize = 1 << 16;
+
+/// Mini blocks per block for Parquet streams: two mini blocks of 64 make the smallest
+/// valid block of 128 values.
+pub(crate) const PARQUET_MINI_BLOCKS: usize = 2;
+
+pub(crate) fn write_var_int<T: Write>(
+    io: &mut T,
+    format: Format,
+    value: u64,
+) -> anyhow::Result<()> {
+    match format {
+        Format::Native => io.write_vlq(value),
+        Format::Parquet => uleb128::write(io, value),
+    }
+    .map_err(|e| anyhow::anyhow!("write_var_int failed {}, {:?}", value, e))
+}
+
+pub(crate) fn read_var_int<T: Read>(io: &mut T, format: Format) -> anyhow::Result<u64> {
+    match format {
+        Format::Native => read_vlq(io),
+        Format::Parquet => uleb128::read(io).map_err(|e| anyhow::anyhow!(e)),
+    }
+}
+
+/// Reads a `vlq` varint of at most `MAX_VAR_INT_LEN` bytes. `vlq` itself panics on longer
+/// ones, which corrupted input can contain.
+pub(crate) fn read_vlq<T: Read>(io: &mut T) -> anyhow::Result<u64> {
+    let mut buffer = [0u8; MAX_VAR_INT_LEN];
+    for len in 1..=MAX_VAR_INT_LEN {
+        io.read_exact(&mut buffer[len - 1..len])
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if buffer[len - 1] & 0x80 != 0 {
+            return (&buffer[..len]).read_vlq().map_err(|e| anyhow::anyhow!(e));
+        }
+    }
+    anyhow::bail!("Varint longer than {} bytes", MAX_VAR_INT_LEN)
+}
+
+/// Longest varint of a `u64` in either format.
+pub(crate) const MAX_VAR_INT_LEN: usize = 10;
+
+/// Writes `value` padded with empty groups to `MAX_VAR_INT_LEN` bytes, so it can be
+/// overwritten in place once the final value is known. Both formats decode it like a
+/// minimal varint.
+pub(crate) fn write_padded_var_int<T: Write>(
+    io: &mut T,
+    format: Format,
+    value: u64,
+) -> anyhow::Result<()> {
+    let mut buffer = [0u8; MAX_VAR_INT_LEN];
+    for (i, byte) in buffer.iter_mut().enumerate() {
+        *byte = (value >> (7 * i)) as u8 & 0x7f;
+    }
+    match format {
+        // `vlq` marks the last byte, LEB128 every byte but the last.
+        Format::Native => buffer[MAX_VAR_INT_LEN - 1] |= 0x80,
+        Format::Parquet => buffer[..MAX_VAR_INT_LEN - 1]
+            .iter_mut()
+            .for_each(|byte| *byte |= 0x80),
+    }
+    io.write_all(&buffer)
+        .map_err(|e| anyhow::anyhow!("write_padded_var_int failed {}, {:?}", value, e))
+}
+
+pub(crate) fn write_zig_zag_var_int<T: Write>(
+    io: &mut T,
+    format: Format,
+    value: i64,
+) -> anyhow::Result<()> {
+    write_var_int(io, format, zig_zag::encode64(value))
+}
+
+pub(crate) fn decode_zig_zag_var_int<T: Read>(io: &mut T, format: Format) -> anyhow::Result<i64> {
+    Ok(zig_zag::decode64(read_var_int(io, format)?))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Header {
+    pub block_size: usize,
+    pub mini_blocks: usize,
+    pub total_count: usize,
+    pub flags: i64,
+    pub first_value: i64,
+    /// `None` for an unversioned header.
+    pub version: Option<u8>,
+}
+
+impl Header {
+    pub fn mini_block_size(&self) -> usize {
+        self.block_size / self.mini_blocks
+    }
+
+    pub fn write<T: Write>(&self, io: &mut T, format: Format) -> anyhow::Result<()> {
+        self.write_options(io, format)?;
+        write_var_int(io, format, self.total_count as u64)?;
+
+        write_zig_zag_var_int(io, format, self.first_value)
+    }
+
+    /// Writes the header with the total count and first value padded, so it can be
+    /// rewritten in place with their final values.
+    pub fn write_padded<T: Write>(&self, io: &mut T, format: Format) -> anyhow::Result<()> {
+        self.write_options(io, format)?;
+        write_padded_var_int(io, format, self.total_count as u64)?;
+
+        write_padded_var_int(io, format, zig_zag::encode64(self.first_value))
+    }
+
+    /// Checks the block layout is one the decoder for `format` accepts.
+    pub fn validate(&self, format: Format) -> anyhow::Result<()> {
+        let valid = self.mini_blocks > 0
+            && self.block_size > 0
+            && self.block_size <= MAX_BLOCK_SIZE
+            && self.block_size.is_multiple_of(self.mini_blocks)
+            && match format {
+                Format::Native => self.mini_block_size() == BLOCK_LEN,
+                Format::Parquet => {
+                    self.block_size.is_multiple_of(PARQUET_BLOCK_MULTIPLE)
+                        && self
+                            .mini_block_size()
+                            .is_multiple_of(PARQUET_MINI_BLOCK_MULTIPLE)
+                }
+            };
+        if !valid {
+            anyhow::bail!("Invalid header {} {}", self.block_size, self.mini_blocks)
+        }
+        Ok(())
+    }
+
+    fn write_options<T: Write>(&self, io: &mut T, format: Format) -> anyhow::Result<()> {
+        self.validate(format)?;
+        if format == Format::Parquet && self.flags != 0 {
+            anyhow::bail!("Parquet streams can't carry header flags {}", self.flags)
+        }
+        match self.version {
+            Some(version) => {
+                if format == Format::Parquet {
+                    anyhow::bail!("Parquet streams can't carry a versioned header")
+                }
+                io.write_all(&HEADER_MAGIC)
+                    .map_err(|e| anyhow::anyhow!(e))?;
+                io.write_all(&[version]).map_err(|e| anyhow::anyhow!(e))?;
+                write_var_int(io, format, self.flags as u64)?;
+                write_var_int(io, format, self.block_size as u64)?;
+            }
+            None => write_var_int(io, format, self.block_size as u64 | self.flags as u64)?,
+        }
+        write_var_int(io, format, self.mini_blocks as u64)
+    }
+
+    pub fn read<T: Read>(io: &mut T, format: Format) -> anyhow::Result<Self> {
+        let mut first = [0u8; 1];
+        io.read_exact(&mut first).map_err(|e| anyhow::anyhow!(e))?;
+
+        let (version, block_size, flags) =
+            if format == Format::Native && first[0] == HEADER_MAGIC[0] {
+                let version = read_version(io)?;
+                let flags = read_var_int(io, format)? as i64;
+                (Some(version), read_var_int(io, format)? as i64, flags)
+            } else {
+                let block_size = read_var_int(&mut first.chain(&mut *io), format)? as i64;
+                match format {
+                    Format::Native => (
+                        None,
+                        block_size & !HEADER_FLAGS_MASK,
+                        block_size & HEADER_FLAGS_MASK,
+                    ),
+                    Format::Parquet => (None, block_size, 0),
+                }
+            };
+        let mini_blocks = read_var_int(io, format)? as i64;
+        let total_count = read_var_int(io, format)? as i64;
+
+        if block_size <= 0 || mini_blocks <= 0 || block_size % mini_blocks != 0 {
+            anyhow::bail!("Invalid header {} {}", block_size, mini_blocks)
+        }
+        if total_count < 0 {
+            anyhow::bail!("Invalid total count {}", total_count)
+        }
+
+        let header = Self {
+            block_size: block_size as usize,
+            mini_blocks: mini_blocks as usize,
+            total_count: total_count as usize,
+            flags,
+            first_value: decode_zig_zag_var_int(io, format)?,
+            version,
+        };
+        header.validate(format)?;
+
+        Ok(header)
+    }
+}
+
+/// Reads the rest of the magic and the version of a versioned header.
+fn read_version<T: Read>(io: &mut T) -> anyhow::Result<u8> {
+    let mut magic = HEADER_MAGIC;
+    io.read_exact(&mut magic[1..])
+        .map_err(|e| anyhow::anyhow!(e))?;
+    if magic != HEADER_MAGIC {
+        anyhow::bail!("Invalid header magic {:?}", magic)
+    }
+
+    let mut version = [0u8; 1];
+    io.read_exact(&mut version)
+        .map_err(|e| anyhow::anyhow!(e))?;
+    if version[0] == 0 || version[0] > HEADER_VERSION {
+        anyhow::bail!("Unsupported header version {}", version[0])
+    }
+    Ok(version[0])
+}
+
+/// Writes the bit widths of all mini blocks in `values` followed by the packed mini blocks,
+/// and returns the bit widths. `values` must hold a whole number of mini blocks.
+pub(crate) fn write_mini_blocks<T: Write>(io: &mut T, values: &[u64]) -> anyhow::Result<Vec<u8>> {
+    let bit_widths: Vec<u8> = values.chunks(BLOCK_LEN).map(num_bits).collect();
+    io.write_all(&bit_widths).map_err(|e| anyhow::anyhow!(e))?;
+
+    let mut compressed = vec![0u8; BLOCK_LEN * 8];
+    for (mini_block, bit_width) in values.chunks(BLOCK_LEN).zip(&bit_widths) {
+        if *bit_width > 0 {
+            let len = bitpacker64::pack(mini_block, &mut compressed, *bit_width);
+            io.write_all(&compressed[..len])
+                .map_err(|e| anyhow::anyhow!(e))?;
+        }
+    }
+    Ok(bit_widths)
+}
+
+pub(crate) fn read_bit_widths<T: Read>(io: &mut T, mini_blocks: usize) -> anyhow::Result<Vec<u8>> {
+    let mut bit_widths = vec![0; mini_blocks];
+    io.read_exact(&mut bit_widths)
+        .map_err(|e| anyhow::anyhow!(e))?;
+    check_bit_widths(&bit_widths)?;
+    Ok(bit_widths)
+}
+
+pub(crate) fn check_bit_widths(bit_widths: &[u8]) -> anyhow::Result<()> {
+    if let Some(bit_width) = bit_widths.iter().find(|bit_width| **bit_width > 64) {
+        anyhow::bail!("Invalid bit width {}", bit_width)
+    }
+    Ok(())
+}
+
+/// Reads one packed mini block of `bit_width` into `unpacked`, whose length is the mini
+/// block size.
+pub(crate) fn read_mini_block<T: Read>(
+    io: &mut T,
+    bit_width: u8,
+    packed: &mut Vec<u8>,
+    unpacked: &mut [u64],
+) -> anyhow::Result<()> {
+    packed.resize(packed_len(unpacked.len(), bit_width), 0);
+    io.read_exact(packed).map_err(|e| anyhow::anyhow!(e))?;
+    unpack_mini_block(packed, bit_width, unpacked);
+    Ok(())
+}
+
+/// Size in bytes of `len` values packed with `bit_width`.
+pub(crate) fn packed_len(len: usize, bit_width: u8) -> usize {
+    len * bit_width as usize / 8
+}
+
+/// Unpacks a mini block of `unpacked.len()` values from `packed`. Mini blocks that aren't
+/// a multiple of `BLOCK_LEN` values (Parquet allows 32) are unpacked through a zero padded
+/// copy on the stack.
+pub(crate) fn unpack_mini_block(packed: &[u8], bit_width: u8, unpacked: &mut [u64]) {
+    if bit_width == 0 {
+        unpacked.fill(0);
+        return;
+    }
+
+    let chunk_len = packed_len(BLOCK_LEN, bit_width);
+    for (packed, unpacked) in packed.chunks(chunk_len).zip(unpacked.chunks_mut(BLOCK_LEN)) {
+        if unpacked.len() == BLOCK_LEN {
+            bitpacker64::unpack(packed, unpacked, bit_width);
+        } else {
+            let mut padded = [0u8; BLOCK_LEN * 8];
+            padded[..packed.len()].copy_from_slice(packed);
+            let mut chunk = [0u64; BLOCK_LEN];
+            bitpacker64::unpack(&padded, &mut chunk, bit_width);
+            unpacked.copy_from_slice(&chunk[..unpacked.len()]);
+        }
+    }
+}
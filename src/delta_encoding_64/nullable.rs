@@ -0,0 +1,180 @@
+use crate::delta_encoding_64::{read_vlq, Decoder, Encoder, Mode};
+use std::io::{Read, Write};
+use vlq::WriteVlqExt;
+
+/// Encodes optional values as a validity bitmap followed by a delta stream of only the
+/// present values, so nulls don't distort the deltas.
+///
+/// The bitmap has one bit per value, least significant bit first, set when the value is
+/// present; the same layout Arrow uses for its null buffers.
+#[derive(Debug, Clone)]
+pub struct NullableEncoder {
+    pub validity: Vec<u8>,
+    pub total_count: usize,
+    pub encoder: Encoder,
+}
+
+impl NullableEncoder {
+    pub fn new(mini_blocks: usize) -> Self {
+        Self {
+            validity: vec![],
+            total_count: 0,
+            encoder: Encoder::new(mini_blocks),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.encoder = self.encoder.with_mode(mode);
+        self
+    }
+
+    pub fn write_integer(&mut self, value: Option<i64>) -> anyhow::Result<()> {
+        if self.total_count.is_multiple_of(8) {
+            self.validity.push(0);
+        }
+        if let Some(value) = value {
+            *self.validity.last_mut().unwrap() |= 1 << (self.total_count % 8);
+            self.encoder.write_integer(value)?;
+        }
+        self.total_count += 1;
+        Ok(())
+    }
+
+    pub fn null_count(&self) -> usize {
+        self.total_count - self.encoder.total_count
+    }
+
+    /// Writes the stream and resets the encoder for the next one, even if writing fails.
+    pub fn write<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        let result = self.write_stream(io);
+        self.reset();
+        result
+    }
+
+    fn write_stream<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        io.write_vlq(self.total_count as i64)
+            .map_err(|e| anyhow::anyhow!("Total size failed {}, {:?}", self.total_count, e))?;
+        io.write_all(&self.validity)
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        self.encoder.write(io)
+    }
+
+    /// Clears all written values, keeping the options and the allocated buffers.
+    pub fn reset(&mut self) {
+        self.validity.clear();
+        self.total_count = 0;
+        self.encoder.reset();
+    }
+}
+
+#[derive(Debug)]
+pub struct NullableDecoder<T: Read> {
+    pub validity: Vec<u8>,
+    pub total_count: usize,
+    pub values_read: usize,
+    pub decoder: Decoder<T>,
+}
+
+impl<T: Read> NullableDecoder<T> {
+    pub fn new(mut io: T) -> anyhow::Result<Self> {
+        let total_count = read_vlq(&mut io)?;
+        let total_count =
+            usize::try_from(total_count).map_err(|e| anyhow::anyhow!("Invalid count {}", e))?;
+
+        // Read through `take` so a corrupted count can't allocate more than the input has.
+        let validity_len = total_count.div_ceil(8);
+        let mut validity = vec![];
+        io.by_ref()
+            .take(validity_len as u64)
+            .read_to_end(&mut validity)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if validity.len() != validity_len {
+            anyhow::bail!(
+                "Validity bitmap truncated, {} of {} bytes",
+                validity.len(),
+                validity_len
+            )
+        }
+
+        let decoder = Decoder::new(io)?;
+        let present: usize = validity.iter().map(|byte| byte.count_ones() as usize).sum();
+        if present != decoder.total_count {
+            anyhow::bail!(
+                "Validity bitmap has {} values, stream has {}",
+                present,
+                decoder.total_count
+            )
+        }
+
+        Ok(Self {
+            validity,
+            total_count,
+            values_read: 0,
+            decoder,
+        })
+    }
+
+    pub fn is_valid(&self, index: usize) -> bool {
+        self.validity[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    pub fn null_count(&self) -> usize {
+        self.total_count - self.decoder.total_count
+    }
+
+    pub fn all_read(&self) -> bool {
+        self.values_read == self.total_count
+    }
+
+    pub fn read_integer(&mut self) -> anyhow::Result<Option<i64>> {
+        if self.all_read() {
+            anyhow::bail!("All values read");
+        }
+        let valid = self.is_valid(self.values_read);
+        self.values_read += 1;
+
+        if valid {
+            Ok(Some(self.decoder.read_integer()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn read_integers(&mut self) -> anyhow::Result<Vec<Option<i64>>> {
+        let mut values = vec![None; self.total_count - self.values_read];
+
+        for value in values.iter_mut() {
+            *value = self.read_integer()?
+        }
+
+        Ok(values)
+    }
+
+    /// Fills `values` with the next values, writing 0 for nulls, and returns how many were
+    /// read. Bit `i` of `validity` is set when `values[i]` is present, in the same layout
+    /// as [`NullableEncoder::validity`].
+    pub fn read_into(&mut self, values: &mut [i64], validity: &mut [u8]) -> anyhow::Result<usize> {
+        let count = values.len().min(self.total_count - self.values_read);
+        if validity.len() < count.div_ceil(8) {
+            anyhow::bail!(
+                "Validity of {} bytes can't hold {} values",
+                validity.len(),
+                count
+            )
+        }
+        validity[..count.div_ceil(8)].fill(0);
+
+        for (i, value) in values[..count].iter_mut().enumerate() {
+            match self.read_integer()? {
+                Some(present) => {
+                    *value = present;
+                    validity[i / 8] |= 1 << (i % 8);
+                }
+                None => *value = 0,
+            }
+        }
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,137 @@
+//! Stores a `Vec<i64>` field delta encoded, with `#[serde(with = "serde_delta")]`.
+//!
+//! The encoded stream is written with `serialize_bytes` for binary formats and as a base64
+//! string for human-readable ones.
+
+use crate::delta_encoding_64::{DeltaVec, SliceDecoder};
+use serde::de::Error;
+use serde::{Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(values: &[i64], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut delta_vec = DeltaVec::new();
+    delta_vec.extend(values.to_vec());
+    bytes::serialize(delta_vec.as_bytes(), serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<i64>, D::Error> {
+    let bytes = bytes::deserialize(deserializer)?;
+    decode(&bytes).map_err(D::Error::custom)
+}
+
+/// Decodes a whole stream, failing if it is invalid or followed by other bytes. No bytes
+/// at all are an empty stream.
+///
+/// Values are collected one at a time rather than allocated up front, so a corrupted total
+/// count can't request more memory than the bytes are able to hold.
+pub(crate) fn decode(bytes: &[u8]) -> anyhow::Result<Vec<i64>> {
+    if bytes.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut decoder = SliceDecoder::new(bytes)?;
+    let mut values = vec![];
+    while !decoder.all_read() {
+        values.push(decoder.read_integer()?);
+    }
+    if !decoder.io.is_empty() {
+        anyhow::bail!("{} bytes after the stream", decoder.io.len())
+    }
+    Ok(values)
+}
+
+/// Byte buffers as bytes or base64, also accepting the sequence of numbers serde derives
+/// for a `Vec<u8>`.
+pub(crate) mod bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+    use std::fmt;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&STANDARD.encode(bytes))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(BytesVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("bytes or a base64 string")
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+            STANDARD.decode(value).map_err(E::custom)
+        }
+
+        fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+            Ok(value.to_vec())
+        }
+
+        fn visit_byte_buf<E: Error>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(value)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default().min(4096));
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+    }
+}
+
+/// Decimals as the strings `rust_decimal` writes for every format. Binary formats are read
+/// with `deserialize_str`, as they don't support the `deserialize_any` that `rust_decimal`
+/// itself uses.
+pub(crate) mod decimal {
+    use rust_decimal::Decimal;
+    use serde::de::{Error, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::fmt;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(value, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        if deserializer.is_human_readable() {
+            <Decimal as Deserialize>::deserialize(deserializer)
+        } else {
+            deserializer.deserialize_str(DecimalVisitor)
+        }
+    }
+
+    struct DecimalVisitor;
+
+    impl Visitor<'_> for DecimalVisitor {
+        type Value = Decimal;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a decimal string")
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+            Decimal::from_str(value).map_err(E::custom)
+        }
+
+        fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+            let value = std::str::from_utf8(value).map_err(E::custom)?;
+            self.visit_str(value)
+        }
+    }
+}
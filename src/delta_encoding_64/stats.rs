@@ -0,0 +1,60 @@
+use super::layout::{decode_zig_zag_var_int, read_var_int, write_var_int, write_zig_zag_var_int};
+use super::Format;
+use std::io::{Read, Write};
+
+/// Summary of a run of values, kept for every block and the whole stream so readers can
+/// build zone maps and page indexes without decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: i64,
+    pub max: i64,
+    pub count: usize,
+    /// Whether every value is greater than or equal to the one before it.
+    pub non_decreasing: bool,
+}
+
+impl Stats {
+    pub fn new(value: i64) -> Self {
+        Self {
+            min: value,
+            max: value,
+            count: 1,
+            non_decreasing: true,
+        }
+    }
+
+    /// Adds `value`, which follows `previous` in the stream.
+    pub fn push(&mut self, value: i64, previous: i64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.count += 1;
+        self.non_decreasing &= value >= previous;
+    }
+
+    pub(crate) fn write<T: Write>(&self, io: &mut T) -> anyhow::Result<()> {
+        write_var_int(io, Format::Native, self.count as u64)?;
+        write_zig_zag_var_int(io, Format::Native, self.min)?;
+        write_zig_zag_var_int(io, Format::Native, self.max)?;
+        write_var_int(io, Format::Native, self.non_decreasing as u64)
+    }
+
+    pub(crate) fn read<T: Read>(io: &mut T) -> anyhow::Result<Self> {
+        let count = read_var_int(io, Format::Native)? as usize;
+        let min = decode_zig_zag_var_int(io, Format::Native)?;
+        let max = decode_zig_zag_var_int(io, Format::Native)?;
+        let non_decreasing = match read_var_int(io, Format::Native)? {
+            0 => false,
+            1 => true,
+            other => anyhow::bail!("Invalid monotonicity flag {}", other),
+        };
+        if count == 0 || min > max {
+            anyhow::bail!("Invalid stats {} {} {}", count, min, max)
+        }
+        Ok(Self {
+            min,
+            max,
+            count,
+            non_decreasing,
+        })
+    }
+}
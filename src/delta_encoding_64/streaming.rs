@@ -0,0 +1,92 @@
+use crate::delta_encoding_64::checksum::write_checksum;
+use crate::delta_encoding_64::{write_padded_var_int, Encoder, Format, Mode};
+use crate::zig_zag;
+use std::io::{Seek, SeekFrom, Write};
+
+/// Writes every block to `io` as soon as it fills, instead of buffering the whole stream
+/// until the total count is known.
+///
+/// The header is written up front with the total count and first values padded to a fixed
+/// width, and patched in place by [`StreamingEncoder::finish`], so the output decodes with
+/// the regular [`Decoder`](super::Decoder). Only one block is held in memory at a time.
+#[derive(Debug)]
+pub struct StreamingEncoder<W: Write + Seek> {
+    pub encoder: Encoder,
+    pub io: W,
+
+    header_position: u64,
+}
+
+impl<W: Write + Seek> StreamingEncoder<W> {
+    /// Starts a stream at the current position of `io`, using the options of `encoder`,
+    /// which must not hold any values yet. A stats section isn't supported, as it precedes
+    /// the blocks.
+    pub fn new(encoder: Encoder, mut io: W) -> anyhow::Result<Self> {
+        if encoder.total_count != 0 {
+            anyhow::bail!("Encoder already holds {} values", encoder.total_count)
+        }
+        if encoder.stats_section {
+            anyhow::bail!("Streams with a stats section can't be written incrementally")
+        }
+        if encoder.format == Format::Parquet && encoder.mode != Mode::Delta {
+            anyhow::bail!("Parquet format does not support {:?}", encoder.mode)
+        }
+
+        let header_position = io.stream_position().map_err(|e| anyhow::anyhow!(e))?;
+        let mut streaming = Self {
+            encoder,
+            io,
+            header_position,
+        };
+        streaming.write_header()?;
+        Ok(streaming)
+    }
+
+    pub fn write_integer(&mut self, value: i64) -> anyhow::Result<()> {
+        self.encoder.write_integer(value)?;
+        self.write_blocks()
+    }
+
+    /// Flushes the last block, patches the header and returns `io` positioned after the
+    /// stream.
+    pub fn finish(mut self) -> anyhow::Result<W> {
+        self.encoder.flush()?;
+        self.write_blocks()?;
+
+        let end = self.io.stream_position().map_err(|e| anyhow::anyhow!(e))?;
+        self.io
+            .seek(SeekFrom::Start(self.header_position))
+            .map_err(|e| anyhow::anyhow!(e))?;
+        self.write_header()?;
+        self.io
+            .seek(SeekFrom::Start(end))
+            .map_err(|e| anyhow::anyhow!(e))?;
+        self.io.flush().map_err(|e| anyhow::anyhow!(e))?;
+        Ok(self.io)
+    }
+
+    fn write_header(&mut self) -> anyhow::Result<()> {
+        let format = self.encoder.format;
+        let mut header = vec![];
+        self.encoder.header().write_padded(&mut header, format)?;
+        if self.encoder.mode == Mode::DeltaOfDelta {
+            let first_delta = zig_zag::encode64(self.encoder.first_delta);
+            write_padded_var_int(&mut header, format, first_delta)?;
+        }
+        if self.encoder.checksums {
+            let checksum = crc32c::crc32c(&header);
+            write_checksum(&mut header, checksum)?;
+        }
+        self.io.write_all(&header).map_err(|e| anyhow::anyhow!(e))
+    }
+
+    fn write_blocks(&mut self) -> anyhow::Result<()> {
+        let blocks = self.encoder.blocks_buffer.get_mut();
+        if !blocks.is_empty() {
+            self.io.write_all(blocks).map_err(|e| anyhow::anyhow!(e))?;
+            blocks.clear();
+            self.encoder.blocks_buffer.set_position(0);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+//! Parquet's `DELTA_LENGTH_BYTE_ARRAY` encoding.
+//!
+//! The lengths of all values are written first as a `DELTA_BINARY_PACKED` stream,
+//! followed by the concatenated bytes of every value.
+
+use crate::delta_encoding_64;
+use crate::delta_encoding_64::{Format, PARQUET_MINI_BLOCKS};
+use std::io::Write;
+
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    pub lengths: delta_encoding_64::Encoder,
+    pub data: Vec<u8>,
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self {
+            lengths: delta_encoding_64::Encoder::new(PARQUET_MINI_BLOCKS)
+                .with_format(Format::Parquet),
+            data: vec![],
+        }
+    }
+
+    pub fn write_value(&mut self, value: &[u8]) -> anyhow::Result<()> {
+        self.lengths.write_integer(value.len() as i64)?;
+        self.data.extend_from_slice(value);
+        Ok(())
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.lengths.total_count
+    }
+
+    pub fn write<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        self.lengths.flush()?;
+        self.lengths.write(io)?;
+
+        let data = std::mem::take(&mut self.data);
+        io.write_all(&data).map_err(|e| anyhow::anyhow!(e))?;
+        Ok(())
+    }
+}
+
+/// Decodes values as slices of the input, without copying them.
+#[derive(Debug, Clone)]
+pub struct Decoder<'a> {
+    pub lengths: Vec<usize>,
+    pub data: &'a [u8],
+    pub values_read: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Decodes the lengths in `data`; the bytes of the values must follow them directly.
+    pub fn new(mut data: &'a [u8]) -> anyhow::Result<Self> {
+        let lengths = delta_encoding_64::Decoder::with_format(&mut data, Format::Parquet)?
+            .read_integers()?
+            .into_iter()
+            .map(|len| usize::try_from(len).map_err(|_| anyhow::anyhow!("Invalid length {}", len)))
+            .collect::<anyhow::Result<Vec<usize>>>()?;
+
+        let total_len = lengths
+            .iter()
+            .try_fold(0usize, |total, len| total.checked_add(*len));
+        match total_len {
+            Some(total_len) if total_len <= data.len() => {}
+            _ => anyhow::bail!("Byte array data truncated, expected {:?} bytes", total_len),
+        }
+
+        Ok(Self {
+            lengths,
+            data,
+            values_read: 0,
+        })
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.lengths.len()
+    }
+
+    pub fn all_read(&self) -> bool {
+        self.values_read == self.lengths.len()
+    }
+
+    pub fn read_value(&mut self) -> anyhow::Result<&'a [u8]> {
+        if self.all_read() {
+            anyhow::bail!("All values read");
+        }
+        let (value, rest) = self.data.split_at(self.lengths[self.values_read]);
+        self.data = rest;
+        self.values_read += 1;
+        Ok(value)
+    }
+
+    pub fn read_values(&mut self) -> anyhow::Result<Vec<&'a [u8]>> {
+        let mut values = Vec::with_capacity(self.lengths.len() - self.values_read);
+        while !self.all_read() {
+            values.push(self.read_value()?);
+        }
+        Ok(values)
+    }
+}
@@ -0,0 +1,218 @@
+//! Dictionary encoding for low-cardinality integer and byte string columns.
+//!
+//! The encoder assigns every distinct value an index in order of first appearance. The
+//! dictionary page holds the distinct values in Parquet's PLAIN encoding and the indices
+//! are written as one bit width byte followed by an [`rle`](crate::rle) payload, the
+//! layout of Parquet's `RLE_DICTIONARY` data pages. As the last group of a bit packed run
+//! is padded, the indices are prefixed with their count as a varint, which Parquet keeps
+//! in the page header instead.
+
+use crate::delta_encoding_64::read_vlq;
+use crate::rle;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::Write;
+use vlq::WriteVlqExt;
+
+/// A value that can be stored in a dictionary page.
+pub trait DictionaryValue: Clone + Eq + Hash {
+    fn write_plain<T: Write>(&self, io: &mut T) -> anyhow::Result<()>;
+
+    fn read_plain(data: &mut &[u8]) -> anyhow::Result<Self>;
+}
+
+impl DictionaryValue for i64 {
+    fn write_plain<T: Write>(&self, io: &mut T) -> anyhow::Result<()> {
+        io.write_all(&self.to_le_bytes())
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    fn read_plain(data: &mut &[u8]) -> anyhow::Result<Self> {
+        let bytes = take(data, 8)?;
+        Ok(i64::from_le_bytes(bytes.try_into()?))
+    }
+}
+
+/// Byte strings are prefixed with their length as a little-endian `u32`.
+impl DictionaryValue for Vec<u8> {
+    fn write_plain<T: Write>(&self, io: &mut T) -> anyhow::Result<()> {
+        let len = u32::try_from(self.len())?;
+        io.write_all(&len.to_le_bytes())
+            .map_err(|e| anyhow::anyhow!(e))?;
+        io.write_all(self).map_err(|e| anyhow::anyhow!(e))
+    }
+
+    fn read_plain(data: &mut &[u8]) -> anyhow::Result<Self> {
+        let len = u32::from_le_bytes(take(data, 4)?.try_into()?);
+        Ok(take(data, len as usize)?.to_vec())
+    }
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> anyhow::Result<&'a [u8]> {
+    if data.len() < len {
+        anyhow::bail!("Dictionary page truncated, {} < {}", data.len(), len)
+    }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+/// Number of bits needed to store indices into a dictionary of `len` values.
+fn index_bit_width(len: usize) -> u8 {
+    let max_index = len.saturating_sub(1) as u64;
+    (64 - max_index.leading_zeros()) as u8
+}
+
+#[derive(Debug, Clone)]
+pub struct Encoder<V: DictionaryValue> {
+    pub dictionary: Vec<V>,
+    pub indices: Vec<u32>,
+
+    lookup: HashMap<V, u32>,
+}
+
+impl<V: DictionaryValue> Default for Encoder<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: DictionaryValue> Encoder<V> {
+    pub fn new() -> Self {
+        Self {
+            dictionary: vec![],
+            indices: vec![],
+            lookup: HashMap::new(),
+        }
+    }
+
+    pub fn write_value<Q>(&mut self, value: &Q) -> anyhow::Result<()>
+    where
+        V: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = V> + ?Sized,
+    {
+        let index = match self.lookup.get(value) {
+            Some(index) => *index,
+            None => {
+                let index = u32::try_from(self.dictionary.len())?;
+                self.dictionary.push(value.to_owned());
+                self.lookup.insert(value.to_owned(), index);
+                index
+            }
+        };
+        self.indices.push(index);
+        Ok(())
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn bit_width(&self) -> u8 {
+        index_bit_width(self.dictionary.len())
+    }
+
+    /// Writes the distinct values in PLAIN encoding, in index order.
+    pub fn write_dictionary<T: Write>(&self, io: &mut T) -> anyhow::Result<()> {
+        for value in &self.dictionary {
+            value.write_plain(io)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the number of indices and the bit width followed by the RLE/bit-packed
+    /// indices.
+    pub fn write_indices<T: Write>(&self, io: &mut T) -> anyhow::Result<()> {
+        io.write_vlq(self.indices.len() as u64)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let bit_width = self.bit_width();
+        io.write_all(&[bit_width]).map_err(|e| anyhow::anyhow!(e))?;
+
+        let mut encoder = rle::Encoder::new(bit_width);
+        for index in &self.indices {
+            encoder.write_value(*index)?;
+        }
+        encoder.write(io)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Decoder<'a, V: DictionaryValue> {
+    pub dictionary: Vec<V>,
+    pub indices: rle::Decoder<'a>,
+    pub total_count: usize,
+    pub values_read: usize,
+}
+
+impl<'a, V: DictionaryValue> Decoder<'a, V> {
+    pub fn new(dictionary_page: &[u8], indices: &'a [u8]) -> anyhow::Result<Self> {
+        let dictionary = Self::read_dictionary(dictionary_page)?;
+        Self::with_dictionary(dictionary, indices)
+    }
+
+    /// Decodes indices against an already decoded dictionary, which lets several data
+    /// pages of a column chunk share one dictionary page.
+    pub fn with_dictionary(dictionary: Vec<V>, mut indices: &'a [u8]) -> anyhow::Result<Self> {
+        let total_count = usize::try_from(read_vlq(&mut indices)?)?;
+        let (bit_width, indices) = indices
+            .split_first()
+            .ok_or_else(|| anyhow::anyhow!("Missing index bit width"))?;
+        if *bit_width > 32 {
+            anyhow::bail!("Invalid index bit width {}", bit_width)
+        }
+
+        Ok(Self {
+            dictionary,
+            indices: rle::Decoder::new(indices, *bit_width),
+            total_count,
+            values_read: 0,
+        })
+    }
+
+    pub fn read_dictionary(mut dictionary_page: &[u8]) -> anyhow::Result<Vec<V>> {
+        let mut dictionary = vec![];
+        while !dictionary_page.is_empty() {
+            dictionary.push(V::read_plain(&mut dictionary_page)?);
+        }
+        Ok(dictionary)
+    }
+
+    pub fn all_read(&self) -> bool {
+        self.values_read == self.total_count
+    }
+
+    /// Reads raw dictionary indices, for callers that materialize values late, and returns
+    /// how many were read.
+    pub fn get_indices(&mut self, buffer: &mut [u32]) -> anyhow::Result<usize> {
+        let count = buffer.len().min(self.total_count - self.values_read);
+        let read = self.indices.get_batch(&mut buffer[..count])?;
+        if read != count {
+            anyhow::bail!("Indices truncated, {} of {} left", read, count)
+        }
+        self.values_read += read;
+        Ok(read)
+    }
+
+    /// Reads up to `count` values, looking every index up in the dictionary.
+    pub fn read_values(&mut self, count: usize) -> anyhow::Result<Vec<V>> {
+        let mut indices = vec![0; count.min(self.total_count - self.values_read)];
+        let read = self.get_indices(&mut indices)?;
+
+        indices[..read]
+            .iter()
+            .map(|index| {
+                self.dictionary
+                    .get(*index as usize)
+                    .cloned()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Index {} out of bounds for dictionary of {}",
+                            index,
+                            self.dictionary.len()
+                        )
+                    })
+            })
+            .collect()
+    }
+}
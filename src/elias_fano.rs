@@ -0,0 +1,277 @@
+//! Elias-Fano encoding of monotonically non-decreasing sequences.
+//!
+//! Every value is split into `low_bits` lower bits, stored verbatim in a packed array, and
+//! the remaining upper bits, stored in unary: value `i` sets bit `(value >> low_bits) + i`
+//! of the upper bit vector. With `low_bits = floor(log2(universe / len))` this takes less
+//! than `2 + low_bits` bits per value.
+//!
+//! Positions of every `SAMPLE_RATE`-th one and zero in the upper bits are sampled, so
+//! [`EliasFano::get`] and [`EliasFano::next_geq`] start scanning at the closest sample
+//! rather than at the start. The scan stays short for evenly spread values, but crosses
+//! every zero (or one) between two samples, which clustered values can make long.
+
+use crate::delta_encoding_64::read_vlq;
+use std::io::{Read, Write};
+use vlq::WriteVlqExt;
+
+const SAMPLE_RATE: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EliasFano {
+    len: usize,
+    low_bits: u8,
+    lower: Vec<u64>,
+    upper: Vec<u64>,
+    upper_len: usize,
+    ones: Vec<usize>,
+    zeros: Vec<usize>,
+}
+
+impl EliasFano {
+    pub fn new(values: &[u64]) -> anyhow::Result<Self> {
+        if let Some(window) = values.windows(2).find(|window| window[0] > window[1]) {
+            anyhow::bail!("Values not sorted, {} > {}", window[0], window[1])
+        }
+
+        let len = values.len();
+        let universe = values
+            .last()
+            .map(|last| last.saturating_add(1))
+            .unwrap_or_default();
+        let low_bits = if len == 0 || universe <= len as u64 {
+            0
+        } else {
+            (universe / len as u64).ilog2() as u8
+        };
+
+        let upper_len = len + (universe >> low_bits) as usize + 1;
+        let mut lower = vec![0u64; (len * low_bits as usize).div_ceil(64)];
+        let mut upper = vec![0u64; upper_len.div_ceil(64)];
+
+        for (index, value) in values.iter().enumerate() {
+            set_bits(&mut lower, index * low_bits as usize, low_bits, *value);
+            let position = (value >> low_bits) as usize + index;
+            upper[position / 64] |= 1 << (position % 64);
+        }
+
+        Ok(Self::from_parts(len, low_bits, lower, upper, upper_len))
+    }
+
+    fn from_parts(
+        len: usize,
+        low_bits: u8,
+        lower: Vec<u64>,
+        upper: Vec<u64>,
+        upper_len: usize,
+    ) -> Self {
+        let mut ones = vec![];
+        let mut zeros = vec![];
+        let (mut ones_seen, mut zeros_seen) = (0, 0);
+        for position in 0..upper_len {
+            if upper[position / 64] & (1 << (position % 64)) != 0 {
+                if ones_seen % SAMPLE_RATE == 0 {
+                    ones.push(position);
+                }
+                ones_seen += 1;
+            } else {
+                if zeros_seen % SAMPLE_RATE == 0 {
+                    zeros.push(position);
+                }
+                zeros_seen += 1;
+            }
+        }
+
+        Self {
+            len,
+            low_bits,
+            lower,
+            upper,
+            upper_len,
+            ones,
+            zeros,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Size of the encoded lower and upper bits in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        (self.lower.len() + self.upper.len()) * 8
+    }
+
+    pub fn get(&self, index: usize) -> Option<u64> {
+        if index >= self.len {
+            return None;
+        }
+        let position = self.select(index, true)?;
+        Some(self.value_at(index, position))
+    }
+
+    /// Returns the index and value of the first value that is greater than or equal to `x`.
+    pub fn next_geq(&self, x: u64) -> Option<(usize, u64)> {
+        let high = (x >> self.low_bits) as usize;
+        // Bucket `high` starts after the `high`-th zero of the upper bits.
+        let start = if high == 0 {
+            0
+        } else {
+            self.select(high - 1, false)? + 1
+        };
+
+        let mut index = start - high;
+        let mut position = start;
+        while index < self.len {
+            position = self.next_one(position)?;
+            let value = self.value_at(index, position);
+            if value >= x {
+                return Some((index, value));
+            }
+            index += 1;
+            position += 1;
+        }
+        None
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        let mut position = 0;
+        (0..self.len).map(move |index| {
+            position = self.next_one(position).unwrap_or_default();
+            let value = self.value_at(index, position);
+            position += 1;
+            value
+        })
+    }
+
+    pub fn write<T: Write>(&self, io: &mut T) -> anyhow::Result<()> {
+        io.write_vlq(self.len as u64)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        io.write_vlq(self.low_bits)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        io.write_vlq(self.upper_len as u64)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        for word in self.lower.iter().chain(&self.upper) {
+            io.write_all(&word.to_le_bytes())
+                .map_err(|e| anyhow::anyhow!(e))?;
+        }
+        Ok(())
+    }
+
+    pub fn read<T: Read>(io: &mut T) -> anyhow::Result<Self> {
+        let len = usize::try_from(read_vlq(io)?)?;
+        let low_bits = u8::try_from(read_vlq(io)?)?;
+        let upper_len = usize::try_from(read_vlq(io)?)?;
+        if low_bits >= 64 || upper_len < len {
+            anyhow::bail!(
+                "Invalid Elias-Fano header {} {} {}",
+                len,
+                low_bits,
+                upper_len
+            )
+        }
+
+        // Read through `take` so a corrupted header can't allocate more than the input has.
+        let mut read_words = |count: usize| -> anyhow::Result<Vec<u64>> {
+            let byte_count = count
+                .checked_mul(8)
+                .ok_or_else(|| anyhow::anyhow!("Too many words {}", count))?;
+            let mut bytes = vec![];
+            io.take(byte_count as u64)
+                .read_to_end(&mut bytes)
+                .map_err(|e| anyhow::anyhow!(e))?;
+            if bytes.len() != byte_count {
+                anyhow::bail!("Words truncated, {} of {} bytes", bytes.len(), byte_count)
+            }
+            Ok(bytes
+                .chunks_exact(8)
+                .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+                .collect())
+        };
+        let lower_len = len
+            .checked_mul(low_bits as usize)
+            .ok_or_else(|| anyhow::anyhow!("Too many lower bits {} {}", len, low_bits))?;
+        let lower = read_words(lower_len.div_ceil(64))?;
+        let upper = read_words(upper_len.div_ceil(64))?;
+
+        let ones: usize = upper.iter().map(|word| word.count_ones() as usize).sum();
+        if ones != len {
+            anyhow::bail!("Upper bits hold {} values, expected {}", ones, len)
+        }
+
+        Ok(Self::from_parts(len, low_bits, lower, upper, upper_len))
+    }
+
+    fn value_at(&self, index: usize, position: usize) -> u64 {
+        let high = (position - index) as u64;
+        let low = get_bits(&self.lower, index * self.low_bits as usize, self.low_bits);
+        (high << self.low_bits) | low
+    }
+
+    /// Position of the `rank`-th one (or zero) in the upper bits.
+    fn select(&self, rank: usize, one: bool) -> Option<usize> {
+        let samples = if one { &self.ones } else { &self.zeros };
+        let mut position = *samples.get(rank / SAMPLE_RATE)?;
+        let mut remaining = rank % SAMPLE_RATE;
+
+        loop {
+            let word_index = position / 64;
+            let word = *self.upper.get(word_index)?;
+            let word = if one { word } else { !word };
+            let word = word & (u64::MAX << (position % 64));
+
+            let count = word.count_ones() as usize;
+            if remaining < count {
+                let found = word_index * 64 + select_in_word(word, remaining);
+                return (found < self.upper_len).then_some(found);
+            }
+            remaining -= count;
+            position = (word_index + 1) * 64;
+        }
+    }
+
+    /// Position of the first one at or after `position`.
+    fn next_one(&self, position: usize) -> Option<usize> {
+        let mut word_index = position / 64;
+        let mut word = *self.upper.get(word_index)? & (u64::MAX << (position % 64));
+        while word == 0 {
+            word_index += 1;
+            word = *self.upper.get(word_index)?;
+        }
+        Some(word_index * 64 + word.trailing_zeros() as usize)
+    }
+}
+
+fn select_in_word(mut word: u64, rank: usize) -> usize {
+    for _ in 0..rank {
+        word &= word - 1;
+    }
+    word.trailing_zeros() as usize
+}
+
+fn set_bits(words: &mut [u64], offset: usize, width: u8, value: u64) {
+    if width == 0 {
+        return;
+    }
+    let value = value & (u64::MAX >> (64 - width));
+    let (index, shift) = (offset / 64, offset % 64);
+    words[index] |= value << shift;
+    if shift + width as usize > 64 {
+        words[index + 1] |= value >> (64 - shift);
+    }
+}
+
+fn get_bits(words: &[u64], offset: usize, width: u8) -> u64 {
+    if width == 0 {
+        return 0;
+    }
+    let (index, shift) = (offset / 64, offset % 64);
+    let mut value = words[index] >> shift;
+    if shift + width as usize > 64 {
+        value |= words[index + 1] << (64 - shift);
+    }
+    value & (u64::MAX >> (64 - width))
+}
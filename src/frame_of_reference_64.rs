@@ -0,0 +1,221 @@
+//! Frame-of-reference encoding for unsorted columns with a narrow value range.
+//!
+//! Shares the header and block layout of [`delta_encoding_64`](crate::delta_encoding_64),
+//! but instead of one minimum delta per block every mini block stores its own minimum
+//! value and the values are bit packed relative to it, without any prefix sums.
+
+use crate::bitpacker64::BLOCK_LEN;
+use crate::delta_encoding_64::{
+    decode_zig_zag_var_int, read_bit_widths, read_mini_block, write_mini_blocks,
+    write_zig_zag_var_int, Format, Header,
+};
+use std::collections::VecDeque;
+use std::io::{Cursor, Read, Write};
+
+/// Header flags marking a frame-of-reference stream, so the delta decoder rejects it.
+const FRAME_OF_REFERENCE_FLAGS: i64 = 0b10;
+
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    pub first_value: i64,
+    pub total_count: usize,
+    pub values: Vec<i64>,
+    pub pos: usize,
+    pub blocks_buffer: Cursor<Vec<u8>>,
+
+    block_size: usize,
+    mini_blocks: usize,
+}
+
+impl Encoder {
+    pub fn new(mini_blocks: usize) -> Self {
+        let block_size = BLOCK_LEN * mini_blocks;
+
+        Self {
+            first_value: 0,
+            total_count: 0,
+            values: vec![0; block_size],
+            pos: 0,
+            blocks_buffer: Default::default(),
+            block_size,
+            mini_blocks,
+        }
+    }
+
+    pub fn write_integer(&mut self, value: i64) -> anyhow::Result<()> {
+        self.total_count += 1;
+
+        if self.total_count == 1 {
+            self.first_value = value;
+            return Ok(());
+        }
+
+        self.values[self.pos] = value;
+        self.pos += 1;
+
+        if self.block_size == self.pos {
+            self.flush_buffer()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        if self.pos == 0 {
+            return Ok(());
+        }
+        // Repeating the last value never widens the mini block it ends up in.
+        let last_value = self.values[self.pos - 1];
+        self.values[self.pos..].fill(last_value);
+        self.pos = self.block_size;
+
+        self.flush_buffer()
+    }
+
+    fn flush_buffer(&mut self) -> anyhow::Result<()> {
+        if self.pos == 0 {
+            return Ok(());
+        }
+
+        let mut offsets = Vec::with_capacity(self.pos);
+        for mini_block in self.values[..self.pos].chunks(BLOCK_LEN) {
+            let min_value = mini_block.iter().copied().min().unwrap_or_default();
+            write_zig_zag_var_int(&mut self.blocks_buffer, Format::Native, min_value)?;
+            offsets.extend(
+                mini_block
+                    .iter()
+                    .map(|value| value.wrapping_sub(min_value) as u64),
+            );
+        }
+        write_mini_blocks(&mut self.blocks_buffer, &offsets)?;
+
+        self.pos = 0;
+        Ok(())
+    }
+
+    pub fn write<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        let result = self.write_stream(io);
+        self.reset();
+        result
+    }
+
+    fn write_stream<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        self.flush()?;
+
+        Header {
+            block_size: self.block_size,
+            mini_blocks: self.mini_blocks,
+            total_count: self.total_count,
+            flags: FRAME_OF_REFERENCE_FLAGS,
+            first_value: self.first_value,
+            version: None,
+        }
+        .write(io, Format::Native)?;
+
+        io.write_all(self.blocks_buffer.get_ref())
+            .map_err(|e| anyhow::anyhow!(e))?;
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.first_value = 0;
+        self.total_count = 0;
+        self.pos = 0;
+        self.blocks_buffer.get_mut().clear();
+        self.blocks_buffer.set_position(0);
+    }
+}
+
+#[derive(Debug)]
+pub struct Decoder<T: Read> {
+    pub first_value: i64,
+    pub total_count: usize,
+    pub min_values: VecDeque<i64>,
+    pub bit_widths: VecDeque<u8>,
+    pub values: VecDeque<i64>,
+    pub values_read: usize,
+    pub io: T,
+
+    mini_blocks: usize,
+    packed: Vec<u8>,
+}
+
+impl<T: Read> Decoder<T> {
+    pub fn new(mut io: T) -> anyhow::Result<Self> {
+        let header = Header::read(&mut io, Format::Native)?;
+        if header.flags != FRAME_OF_REFERENCE_FLAGS {
+            anyhow::bail!("Not a frame-of-reference stream {}", header.flags)
+        }
+
+        Ok(Self {
+            first_value: header.first_value,
+            total_count: header.total_count,
+            min_values: Default::default(),
+            bit_widths: Default::default(),
+            values: Default::default(),
+            values_read: 0,
+            io,
+            mini_blocks: header.mini_blocks,
+            packed: vec![],
+        })
+    }
+
+    pub fn read_integers(&mut self) -> anyhow::Result<Vec<i64>> {
+        // The count comes from the header, so values are pushed as they are read.
+        let mut values = vec![];
+        while !self.all_read() {
+            values.push(self.read_integer()?);
+        }
+        Ok(values)
+    }
+
+    pub fn read_integer(&mut self) -> anyhow::Result<i64> {
+        if self.all_read() {
+            anyhow::bail!("All values read");
+        }
+
+        self.values_read += 1;
+        if self.values_read == 1 {
+            return Ok(self.first_value);
+        }
+
+        if self.values.is_empty() {
+            self.read_values()?;
+        }
+
+        Ok(self.values.pop_front().unwrap_or_default())
+    }
+
+    pub fn all_read(&self) -> bool {
+        self.values_read == self.total_count
+    }
+
+    pub fn read_block(&mut self) -> anyhow::Result<()> {
+        for _ in 0..self.mini_blocks {
+            let min_value = decode_zig_zag_var_int(&mut self.io, Format::Native)?;
+            self.min_values.push_back(min_value);
+        }
+
+        let bit_widths = read_bit_widths(&mut self.io, self.mini_blocks)?;
+        self.bit_widths.extend(bit_widths.iter());
+        Ok(())
+    }
+
+    pub fn read_values(&mut self) -> anyhow::Result<()> {
+        if self.bit_widths.is_empty() {
+            self.read_block()?;
+        }
+
+        let bit_width = self.bit_widths.pop_front().unwrap_or_default();
+        let min_value = self.min_values.pop_front().unwrap_or_default();
+        let mut result = vec![0_u64; BLOCK_LEN];
+        read_mini_block(&mut self.io, bit_width, &mut self.packed, &mut result)?;
+
+        self.values.extend(
+            result
+                .iter()
+                .map(|offset| min_value.wrapping_add(*offset as i64)),
+        );
+
+        Ok(())
+    }
+}
@@ -1,3 +1,14 @@
 pub mod bitpacker64;
+pub mod boolean;
+pub mod byte_stream_split;
+pub mod container;
+pub mod delta_byte_array;
 pub mod delta_encoding_64;
+pub mod delta_length_byte_array;
+pub mod dictionary;
+pub mod elias_fano;
+pub mod frame_of_reference_64;
+pub mod parquet_page;
+pub mod rle;
+pub mod uleb128;
 pub mod zig_zag;
@@ -0,0 +1,335 @@
+//! Parquet data pages (version 1) of `INT64` columns with `DELTA_BINARY_PACKED` values.
+//!
+//! A page is a Thrift compact `PageHeader` followed by the page body: for optional
+//! columns the definition levels, RLE encoded with a bit width of 1 and prefixed by their
+//! length as a little-endian `u32`, then the non-null values as a
+//! [`Format::Parquet`] delta stream. Pages are written uncompressed; a column chunk is a
+//! sequence of pages.
+
+mod thrift;
+
+use crate::bitpacker64::BLOCK_LEN;
+use crate::delta_encoding_64::{Encoder, Format, SliceDecoder, PARQUET_MINI_BLOCKS};
+use crate::rle;
+use std::io::{Read, Write};
+use thrift::{StructReader, StructWriter};
+
+pub const PAGE_TYPE_DATA_PAGE: i32 = 0;
+pub const ENCODING_RLE: i32 = 3;
+pub const ENCODING_DELTA_BINARY_PACKED: i32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataPageHeader {
+    pub num_values: i32,
+    pub encoding: i32,
+    pub definition_level_encoding: i32,
+    pub repetition_level_encoding: i32,
+}
+
+/// The fields of Parquet's `PageHeader` used by data pages. Other fields, like page
+/// statistics, are skipped when reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageHeader {
+    pub page_type: i32,
+    pub uncompressed_page_size: i32,
+    pub compressed_page_size: i32,
+    pub crc: Option<i32>,
+    pub data_page_header: Option<DataPageHeader>,
+}
+
+impl DataPageHeader {
+    fn write<W: Write>(&self, io: &mut W) -> anyhow::Result<()> {
+        let mut fields = StructWriter::default();
+        fields.i32_field(io, 1, self.num_values)?;
+        fields.i32_field(io, 2, self.encoding)?;
+        fields.i32_field(io, 3, self.definition_level_encoding)?;
+        fields.i32_field(io, 4, self.repetition_level_encoding)?;
+        fields.stop(io)
+    }
+
+    fn read<R: Read>(io: &mut R) -> anyhow::Result<Self> {
+        let (mut num_values, mut encoding, mut definition, mut repetition) =
+            (None, None, None, None);
+        let mut fields = StructReader::default();
+        while let Some((id, field_type)) = fields.field_header(io)? {
+            match (id, field_type) {
+                (1, thrift::I32) => num_values = Some(thrift::read_i32(io)?),
+                (2, thrift::I32) => encoding = Some(thrift::read_i32(io)?),
+                (3, thrift::I32) => definition = Some(thrift::read_i32(io)?),
+                (4, thrift::I32) => repetition = Some(thrift::read_i32(io)?),
+                _ => thrift::skip(io, field_type)?,
+            }
+        }
+
+        match (num_values, encoding, definition, repetition) {
+            (Some(num_values), Some(encoding), Some(definition), Some(repetition)) => Ok(Self {
+                num_values,
+                encoding,
+                definition_level_encoding: definition,
+                repetition_level_encoding: repetition,
+            }),
+            _ => anyhow::bail!("Data page header misses required fields"),
+        }
+    }
+}
+
+impl PageHeader {
+    pub fn write<W: Write>(&self, io: &mut W) -> anyhow::Result<()> {
+        let mut fields = StructWriter::default();
+        fields.i32_field(io, 1, self.page_type)?;
+        fields.i32_field(io, 2, self.uncompressed_page_size)?;
+        fields.i32_field(io, 3, self.compressed_page_size)?;
+        if let Some(crc) = self.crc {
+            fields.i32_field(io, 4, crc)?;
+        }
+        if let Some(data_page_header) = &self.data_page_header {
+            fields.field_header(io, 5, thrift::STRUCT)?;
+            data_page_header.write(io)?;
+        }
+        fields.stop(io)
+    }
+
+    pub fn read<R: Read>(io: &mut R) -> anyhow::Result<Self> {
+        let (mut page_type, mut uncompressed, mut compressed) = (None, None, None);
+        let (mut crc, mut data_page_header) = (None, None);
+        let mut fields = StructReader::default();
+        while let Some((id, field_type)) = fields.field_header(io)? {
+            match (id, field_type) {
+                (1, thrift::I32) => page_type = Some(thrift::read_i32(io)?),
+                (2, thrift::I32) => uncompressed = Some(thrift::read_i32(io)?),
+                (3, thrift::I32) => compressed = Some(thrift::read_i32(io)?),
+                (4, thrift::I32) => crc = Some(thrift::read_i32(io)?),
+                (5, thrift::STRUCT) => data_page_header = Some(DataPageHeader::read(io)?),
+                _ => thrift::skip(io, field_type)?,
+            }
+        }
+
+        match (page_type, uncompressed, compressed) {
+            (Some(page_type), Some(uncompressed), Some(compressed)) => Ok(Self {
+                page_type,
+                uncompressed_page_size: uncompressed,
+                compressed_page_size: compressed,
+                crc,
+                data_page_header,
+            }),
+            _ => anyhow::bail!("Page header misses required fields"),
+        }
+    }
+}
+
+/// Buffers the values of one page at a time. `nullable` columns (optional, maximum
+/// definition level 1) get definition levels, required ones don't.
+#[derive(Debug, Clone)]
+pub struct PageWriter {
+    pub levels: rle::Encoder,
+    pub values: Encoder,
+    pub num_values: usize,
+
+    nullable: bool,
+}
+
+impl PageWriter {
+    pub fn new(nullable: bool) -> Self {
+        Self {
+            levels: rle::Encoder::new(1),
+            values: Encoder::new(PARQUET_MINI_BLOCKS).with_format(Format::Parquet),
+            num_values: 0,
+            nullable,
+        }
+    }
+
+    pub fn write_value(&mut self, value: Option<i64>) -> anyhow::Result<()> {
+        match value {
+            Some(value) => self.values.write_integer(value)?,
+            None if !self.nullable => anyhow::bail!("Null in a required column"),
+            None => {}
+        }
+        if self.nullable {
+            self.levels.write_value(value.is_some() as u32)?;
+        }
+        self.num_values += 1;
+        Ok(())
+    }
+
+    /// Writes the buffered values as one page and starts the next.
+    pub fn write_page<W: Write>(&mut self, io: &mut W) -> anyhow::Result<()> {
+        let mut body = vec![];
+        if self.nullable {
+            let mut levels = std::mem::replace(&mut self.levels, rle::Encoder::new(1));
+            let mut encoded_levels = vec![];
+            levels.write(&mut encoded_levels)?;
+            body.extend_from_slice(&(encoded_levels.len() as u32).to_le_bytes());
+            body.extend_from_slice(&encoded_levels);
+        }
+        self.values.write(&mut body)?;
+
+        let page_size = i32::try_from(body.len())
+            .map_err(|_| anyhow::anyhow!("Page of {} bytes too large", body.len()))?;
+        let num_values = i32::try_from(std::mem::take(&mut self.num_values))
+            .map_err(|e| anyhow::anyhow!("Too many values {}", e))?;
+        PageHeader {
+            page_type: PAGE_TYPE_DATA_PAGE,
+            uncompressed_page_size: page_size,
+            compressed_page_size: page_size,
+            crc: None,
+            data_page_header: Some(DataPageHeader {
+                num_values,
+                encoding: ENCODING_DELTA_BINARY_PACKED,
+                definition_level_encoding: ENCODING_RLE,
+                repetition_level_encoding: ENCODING_RLE,
+            }),
+        }
+        .write(io)?;
+        io.write_all(&body).map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+/// Reads the pages of an uncompressed column chunk written by any Parquet writer.
+#[derive(Debug)]
+pub struct PageReader<R: Read> {
+    pub io: R,
+
+    nullable: bool,
+    body: Vec<u8>,
+}
+
+impl<R: Read> PageReader<R> {
+    pub fn new(io: R, nullable: bool) -> Self {
+        Self {
+            io,
+            nullable,
+            body: vec![],
+        }
+    }
+
+    /// Reads the next page, or returns `None` at the end of the column chunk.
+    pub fn read_page(&mut self) -> anyhow::Result<Option<Vec<Option<i64>>>> {
+        let mut first = [0u8; 1];
+        if self.io.read(&mut first).map_err(|e| anyhow::anyhow!(e))? == 0 {
+            return Ok(None);
+        }
+        let header = PageHeader::read(&mut first.chain(&mut self.io))?;
+
+        let data_page_header = match (header.page_type, header.data_page_header) {
+            (PAGE_TYPE_DATA_PAGE, Some(data_page_header)) => data_page_header,
+            (page_type, _) => anyhow::bail!("Unsupported page type {}", page_type),
+        };
+        if data_page_header.encoding != ENCODING_DELTA_BINARY_PACKED {
+            anyhow::bail!("Unsupported value encoding {}", data_page_header.encoding)
+        }
+        if header.compressed_page_size != header.uncompressed_page_size
+            || header.compressed_page_size < 0
+        {
+            anyhow::bail!(
+                "Compressed pages not supported, {} != {}",
+                header.compressed_page_size,
+                header.uncompressed_page_size
+            )
+        }
+
+        // Read through `take` so a corrupted header can't allocate more than the input has.
+        let body_len = header.compressed_page_size as usize;
+        self.body.clear();
+        self.io
+            .by_ref()
+            .take(body_len as u64)
+            .read_to_end(&mut self.body)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if self.body.len() != body_len {
+            anyhow::bail!(
+                "Page body truncated, {} of {} bytes",
+                self.body.len(),
+                body_len
+            )
+        }
+        let num_values = usize::try_from(data_page_header.num_values)
+            .map_err(|e| anyhow::anyhow!("Invalid value count {}", e))?;
+
+        let mut body = self.body.as_slice();
+        let levels = if self.nullable {
+            if data_page_header.definition_level_encoding != ENCODING_RLE {
+                anyhow::bail!(
+                    "Unsupported definition level encoding {}",
+                    data_page_header.definition_level_encoding
+                )
+            }
+            Some(read_levels(&mut body, num_values)?)
+        } else {
+            None
+        };
+
+        let mut decoder = SliceDecoder::with_format(body, Format::Parquet)?;
+        let present = levels.as_ref().map_or(num_values, |levels| {
+            levels.iter().filter(|level| **level == 1).count()
+        });
+        if decoder.total_count != present {
+            anyhow::bail!(
+                "Page has {} values, {} are defined",
+                decoder.total_count,
+                present
+            )
+        }
+
+        // `num_values` comes from the header, so without levels it only sizes the buffer up
+        // to one value per bit of the body.
+        let capacity = levels
+            .as_ref()
+            .map_or(num_values.min(8 * self.body.len()), Vec::len);
+        let mut values = Vec::with_capacity(capacity);
+        match levels {
+            Some(levels) => {
+                for level in levels {
+                    values.push(if level == 1 {
+                        Some(decoder.read_integer()?)
+                    } else {
+                        None
+                    });
+                }
+            }
+            None => {
+                while !decoder.all_read() {
+                    values.push(Some(decoder.read_integer()?));
+                }
+            }
+        }
+        Ok(Some(values))
+    }
+
+    /// Reads all remaining pages.
+    pub fn read_pages(&mut self) -> anyhow::Result<Vec<Option<i64>>> {
+        let mut values = vec![];
+        while let Some(page) = self.read_page()? {
+            values.extend(page);
+        }
+        Ok(values)
+    }
+}
+
+fn read_levels(body: &mut &[u8], num_values: usize) -> anyhow::Result<Vec<u32>> {
+    if body.len() < 4 {
+        anyhow::bail!("Definition levels truncated")
+    }
+    let (len, rest) = body.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    if rest.len() < len {
+        anyhow::bail!("Definition levels of {} bytes truncated", len)
+    }
+    let (encoded, rest) = rest.split_at(len);
+    *body = rest;
+
+    // Decoded in batches, so only levels the body holds are allocated.
+    let mut decoder = rle::Decoder::new(encoded, 1);
+    let mut batch = vec![0u32; BLOCK_LEN];
+    let mut levels = vec![];
+    while levels.len() < num_values {
+        let count = batch.len().min(num_values - levels.len());
+        if decoder.get_batch(&mut batch[..count])? != count {
+            anyhow::bail!(
+                "Page has fewer definition levels than {} values",
+                num_values
+            )
+        }
+        levels.extend_from_slice(&batch[..count]);
+    }
+    Ok(levels)
+}
@@ -0,0 +1,174 @@
+//! The parts of Thrift's compact protocol needed for Parquet page headers.
+//!
+//! Every struct field starts with a byte holding the field id delta from the previous
+//! field in its upper nibble and the field type in the lower one; a delta of 0 means the
+//! zigzag encoded id follows. Integers are zigzag ULEB128 varints and a `STOP` byte ends
+//! the struct.
+
+use crate::{uleb128, zig_zag};
+use std::io::{Read, Write};
+
+pub(crate) const STOP: u8 = 0;
+pub(crate) const BOOLEAN_TRUE: u8 = 1;
+pub(crate) const BOOLEAN_FALSE: u8 = 2;
+pub(crate) const BYTE: u8 = 3;
+pub(crate) const I16: u8 = 4;
+pub(crate) const I32: u8 = 5;
+pub(crate) const I64: u8 = 6;
+pub(crate) const DOUBLE: u8 = 7;
+pub(crate) const BINARY: u8 = 8;
+pub(crate) const LIST: u8 = 9;
+pub(crate) const SET: u8 = 10;
+pub(crate) const MAP: u8 = 11;
+pub(crate) const STRUCT: u8 = 12;
+
+/// Nesting deeper than this is rejected rather than recursed into.
+const MAX_DEPTH: usize = 32;
+
+fn read_byte<R: Read>(io: &mut R) -> anyhow::Result<u8> {
+    let mut byte = [0u8; 1];
+    io.read_exact(&mut byte).map_err(|e| anyhow::anyhow!(e))?;
+    Ok(byte[0])
+}
+
+fn read_varint<R: Read>(io: &mut R) -> anyhow::Result<u64> {
+    uleb128::read(io).map_err(|e| anyhow::anyhow!(e))
+}
+
+/// Writes the fields of one struct, tracking the previous field id.
+#[derive(Debug, Default)]
+pub(crate) struct StructWriter {
+    last_id: i16,
+}
+
+impl StructWriter {
+    pub fn field_header<W: Write>(
+        &mut self,
+        io: &mut W,
+        id: i16,
+        field_type: u8,
+    ) -> anyhow::Result<()> {
+        let delta = id - self.last_id;
+        if (1..=15).contains(&delta) {
+            io.write_all(&[(delta as u8) << 4 | field_type])
+                .map_err(|e| anyhow::anyhow!(e))?;
+        } else {
+            io.write_all(&[field_type])
+                .map_err(|e| anyhow::anyhow!(e))?;
+            uleb128::write(io, zig_zag::encode64(id as i64)).map_err(|e| anyhow::anyhow!(e))?;
+        }
+        self.last_id = id;
+        Ok(())
+    }
+
+    pub fn i32_field<W: Write>(&mut self, io: &mut W, id: i16, value: i32) -> anyhow::Result<()> {
+        self.field_header(io, id, I32)?;
+        uleb128::write(io, zig_zag::encode64(value as i64)).map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn stop<W: Write>(self, io: &mut W) -> anyhow::Result<()> {
+        io.write_all(&[STOP]).map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+/// Reads the field headers of one struct, tracking the previous field id.
+#[derive(Debug, Default)]
+pub(crate) struct StructReader {
+    last_id: i16,
+}
+
+impl StructReader {
+    /// Returns the id and type of the next field, or `None` at the end of the struct.
+    pub fn field_header<R: Read>(&mut self, io: &mut R) -> anyhow::Result<Option<(i16, u8)>> {
+        let byte = read_byte(io)?;
+        let field_type = byte & 0x0f;
+        if field_type == STOP {
+            return Ok(None);
+        }
+        let id = match byte >> 4 {
+            0 => i16::try_from(zig_zag::decode64(read_varint(io)?))
+                .map_err(|e| anyhow::anyhow!("Invalid field id {}", e))?,
+            delta => self
+                .last_id
+                .checked_add(delta as i16)
+                .ok_or_else(|| anyhow::anyhow!("Field id overflows after {}", self.last_id))?,
+        };
+        self.last_id = id;
+        Ok(Some((id, field_type)))
+    }
+}
+
+pub(crate) fn read_i32<R: Read>(io: &mut R) -> anyhow::Result<i32> {
+    let value = zig_zag::decode64(read_varint(io)?);
+    i32::try_from(value).map_err(|_| anyhow::anyhow!("{} out of range for i32", value))
+}
+
+/// Skips a value of `field_type`, including everything nested in it.
+pub(crate) fn skip<R: Read>(io: &mut R, field_type: u8) -> anyhow::Result<()> {
+    skip_value(io, field_type, 0)
+}
+
+fn skip_value<R: Read>(io: &mut R, field_type: u8, depth: usize) -> anyhow::Result<()> {
+    if depth > MAX_DEPTH {
+        anyhow::bail!("Thrift struct nested deeper than {}", MAX_DEPTH)
+    }
+    match field_type {
+        // Boolean fields carry their value in the field type.
+        BOOLEAN_TRUE | BOOLEAN_FALSE => {}
+        BYTE => {
+            read_byte(io)?;
+        }
+        I16 | I32 | I64 => {
+            read_varint(io)?;
+        }
+        DOUBLE => {
+            io.read_exact(&mut [0u8; 8])
+                .map_err(|e| anyhow::anyhow!(e))?;
+        }
+        BINARY => {
+            let len = read_varint(io)?;
+            let skipped = std::io::copy(&mut io.take(len), &mut std::io::sink())
+                .map_err(|e| anyhow::anyhow!(e))?;
+            if skipped != len {
+                anyhow::bail!("Binary truncated, {} of {} bytes", skipped, len)
+            }
+        }
+        LIST | SET => {
+            let header = read_byte(io)?;
+            let len = match header >> 4 {
+                15 => read_varint(io)?,
+                len => len as u64,
+            };
+            let element_type = collection_type(header & 0x0f);
+            for _ in 0..len {
+                skip_value(io, element_type, depth + 1)?;
+            }
+        }
+        MAP => {
+            let len = read_varint(io)?;
+            if len > 0 {
+                let types = read_byte(io)?;
+                for _ in 0..len {
+                    skip_value(io, collection_type(types >> 4), depth + 1)?;
+                    skip_value(io, collection_type(types & 0x0f), depth + 1)?;
+                }
+            }
+        }
+        STRUCT => {
+            let mut reader = StructReader::default();
+            while let Some((_, field_type)) = reader.field_header(io)? {
+                skip_value(io, field_type, depth + 1)?;
+            }
+        }
+        other => anyhow::bail!("Unknown Thrift type {}", other),
+    }
+    Ok(())
+}
+
+/// Booleans in collections take a byte each instead of being folded into a field type.
+fn collection_type(element_type: u8) -> u8 {
+    match element_type {
+        BOOLEAN_TRUE | BOOLEAN_FALSE => BYTE,
+        element_type => element_type,
+    }
+}
@@ -0,0 +1,257 @@
+//! Parquet's run-length / bit-packing hybrid encoding.
+//!
+//! Used for definition/repetition levels and dictionary indices. The stream is a
+//! sequence of runs, each starting with a ULEB128 header whose lowest bit selects the
+//! run type:
+//!
+//! * `count << 1` is followed by a single value repeated `count` times, stored
+//!   little-endian in `ceil(bit_width / 8)` bytes.
+//! * `(groups << 1) | 1` is followed by `groups * 8` values bit packed with
+//!   `bit_width` bits each, least significant bit first.
+//!
+//! The bit packing order matches [`bitpacker64`] on little-endian targets, so whole
+//! mini blocks of 64 values are packed and unpacked with it directly.
+
+use crate::bitpacker64::BLOCK_LEN;
+use crate::{bitpacker64, uleb128};
+use std::io::Write;
+
+/// Number of values in a bit packed group.
+const GROUP_LEN: usize = 8;
+
+/// Runs of at least this many equal values are written as RLE runs.
+const MIN_REPEAT_RUN: usize = GROUP_LEN;
+
+/// Literal values are flushed as a bit packed run once this many are buffered.
+const MAX_BUFFERED_LITERALS: usize = BLOCK_LEN * 8;
+
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    pub literals: Vec<u64>,
+    pub run_value: u64,
+    pub run_len: usize,
+    pub total_count: usize,
+    pub buffer: Vec<u8>,
+
+    bit_width: u8,
+    max_value: u64,
+}
+
+impl Encoder {
+    pub fn new(bit_width: u8) -> Self {
+        assert!(bit_width <= 32, "Bit width {} exceeds 32", bit_width);
+
+        Self {
+            literals: Vec::with_capacity(MAX_BUFFERED_LITERALS),
+            run_value: 0,
+            run_len: 0,
+            total_count: 0,
+            buffer: vec![],
+            bit_width,
+            max_value: (1u64 << bit_width) - 1,
+        }
+    }
+
+    pub fn bit_width(&self) -> u8 {
+        self.bit_width
+    }
+
+    pub fn write_value(&mut self, value: u32) -> anyhow::Result<()> {
+        let value = value as u64;
+        if value > self.max_value {
+            anyhow::bail!("Value {} does not fit in {} bits", value, self.bit_width)
+        }
+        self.total_count += 1;
+
+        if self.run_len > 0 && self.run_value == value {
+            self.run_len += 1;
+            return Ok(());
+        }
+
+        self.end_run()?;
+        self.run_value = value;
+        self.run_len = 1;
+        Ok(())
+    }
+
+    /// Writes all buffered values, padding the last bit packed run with zeros.
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        self.end_run()?;
+        if !self.literals.is_empty() {
+            let padded_len = self.literals.len().div_ceil(GROUP_LEN) * GROUP_LEN;
+            self.literals.resize(padded_len, 0);
+            self.write_literals(padded_len)?;
+        }
+        Ok(())
+    }
+
+    pub fn write<T: Write>(&mut self, io: &mut T) -> anyhow::Result<()> {
+        self.flush()?;
+        let bytes = std::mem::take(&mut self.buffer);
+        io.write_all(&bytes).map_err(|e| anyhow::anyhow!(e))?;
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> anyhow::Result<()> {
+        let run_len = std::mem::take(&mut self.run_len);
+        if run_len < MIN_REPEAT_RUN {
+            self.literals
+                .extend(std::iter::repeat_n(self.run_value, run_len));
+            if self.literals.len() >= MAX_BUFFERED_LITERALS {
+                self.write_literals(self.literals.len() / GROUP_LEN * GROUP_LEN)?;
+            }
+            return Ok(());
+        }
+
+        // Bit packed runs hold whole groups, so complete the last group from this run.
+        let pad = (GROUP_LEN - self.literals.len() % GROUP_LEN) % GROUP_LEN;
+        self.literals
+            .extend(std::iter::repeat_n(self.run_value, pad));
+        self.write_literals(self.literals.len())?;
+
+        self.write_repeated(self.run_value, run_len - pad)
+    }
+
+    fn write_repeated(&mut self, value: u64, count: usize) -> anyhow::Result<()> {
+        uleb128::write(&mut self.buffer, (count as u64) << 1).map_err(|e| anyhow::anyhow!(e))?;
+        let value_bytes = (self.bit_width as usize).div_ceil(8);
+        self.buffer
+            .extend_from_slice(&value.to_le_bytes()[..value_bytes]);
+        Ok(())
+    }
+
+    /// Writes the first `count` buffered literals, a multiple of the group size, as one
+    /// bit packed run.
+    fn write_literals(&mut self, count: usize) -> anyhow::Result<()> {
+        if count == 0 {
+            return Ok(());
+        }
+        let groups = count / GROUP_LEN;
+        uleb128::write(&mut self.buffer, ((groups as u64) << 1) | 1)
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        let mut mini_block = [0u64; BLOCK_LEN];
+        let mut packed = [0u8; BLOCK_LEN * 4];
+        for chunk in self.literals[..count].chunks(BLOCK_LEN) {
+            mini_block[..chunk.len()].copy_from_slice(chunk);
+            mini_block[chunk.len()..].fill(0);
+            bitpacker64::pack(&mini_block, &mut packed, self.bit_width);
+            let len = chunk.len() * self.bit_width as usize / 8;
+            self.buffer.extend_from_slice(&packed[..len]);
+        }
+
+        self.literals.drain(..count);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Decoder<'a> {
+    pub data: &'a [u8],
+    pub repeat_count: usize,
+    pub repeat_value: u32,
+    pub bit_packed_count: usize,
+
+    bit_width: u8,
+    unpacked: [u64; BLOCK_LEN],
+    unpacked_pos: usize,
+    unpacked_len: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(data: &'a [u8], bit_width: u8) -> Self {
+        assert!(bit_width <= 32, "Bit width {} exceeds 32", bit_width);
+
+        Self {
+            data,
+            repeat_count: 0,
+            repeat_value: 0,
+            bit_packed_count: 0,
+            bit_width,
+            unpacked: [0; BLOCK_LEN],
+            unpacked_pos: 0,
+            unpacked_len: 0,
+        }
+    }
+
+    pub fn bit_width(&self) -> u8 {
+        self.bit_width
+    }
+
+    /// Fills `buffer` with the next values and returns how many were read, which is
+    /// less than `buffer.len()` only once the data is exhausted.
+    pub fn get_batch(&mut self, buffer: &mut [u32]) -> anyhow::Result<usize> {
+        let mut read = 0;
+        while read < buffer.len() {
+            if self.repeat_count > 0 {
+                let n = self.repeat_count.min(buffer.len() - read);
+                buffer[read..read + n].fill(self.repeat_value);
+                self.repeat_count -= n;
+                read += n;
+            } else if self.unpacked_pos < self.unpacked_len {
+                let n = (self.unpacked_len - self.unpacked_pos).min(buffer.len() - read);
+                let unpacked = &self.unpacked[self.unpacked_pos..self.unpacked_pos + n];
+                for (value, unpacked) in buffer[read..read + n].iter_mut().zip(unpacked) {
+                    *value = *unpacked as u32;
+                }
+                self.unpacked_pos += n;
+                read += n;
+            } else if self.bit_packed_count > 0 {
+                self.unpack_mini_block()?;
+            } else if !self.read_run_header()? {
+                break;
+            }
+        }
+        Ok(read)
+    }
+
+    fn read_run_header(&mut self) -> anyhow::Result<bool> {
+        if self.data.is_empty() {
+            return Ok(false);
+        }
+        let header = uleb128::read(&mut self.data).map_err(|e| anyhow::anyhow!(e))?;
+        let count = (header >> 1) as usize;
+
+        if header & 1 == 1 {
+            self.bit_packed_count = count * GROUP_LEN;
+        } else {
+            let value_bytes = (self.bit_width as usize).div_ceil(8);
+            if self.data.len() < value_bytes {
+                anyhow::bail!("RLE run value truncated")
+            }
+            let mut value = [0u8; 4];
+            value[..value_bytes].copy_from_slice(&self.data[..value_bytes]);
+            self.data = &self.data[value_bytes..];
+            self.repeat_value = u32::from_le_bytes(value);
+            self.repeat_count = count;
+        }
+        Ok(true)
+    }
+
+    fn unpack_mini_block(&mut self) -> anyhow::Result<()> {
+        let count = self.bit_packed_count.min(BLOCK_LEN);
+        let len = count * self.bit_width as usize / 8;
+        if self.data.len() < len {
+            anyhow::bail!("Bit packed run truncated, {} < {}", self.data.len(), len)
+        }
+
+        if self.bit_width == 0 {
+            self.unpacked.fill(0);
+        } else {
+            let full_len = BLOCK_LEN * self.bit_width as usize / 8;
+            if self.data.len() >= full_len {
+                bitpacker64::unpack(self.data, &mut self.unpacked, self.bit_width);
+            } else {
+                let mut packed = [0u8; BLOCK_LEN * 4];
+                packed[..len].copy_from_slice(&self.data[..len]);
+                bitpacker64::unpack(&packed, &mut self.unpacked, self.bit_width);
+            }
+        }
+
+        self.data = &self.data[len..];
+        self.bit_packed_count -= count;
+        self.unpacked_pos = 0;
+        self.unpacked_len = count;
+        Ok(())
+    }
+}
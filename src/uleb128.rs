@@ -0,0 +1,39 @@
+//! Unsigned LEB128 varints, as used by Parquet and Thrift.
+//!
+//! Unlike the `vlq` crate used for the native stream headers, LEB128 stores the least
+//! significant group first and sets the high bit on every byte except the last one.
+
+use std::io::{Read, Write};
+
+pub fn write<T: Write>(io: &mut T, mut value: u64) -> std::io::Result<()> {
+    let mut buffer = [0u8; 10];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer[len] = byte;
+            len += 1;
+            break;
+        }
+        buffer[len] = byte | 0x80;
+        len += 1;
+    }
+    io.write_all(&buffer[..len])
+}
+
+pub fn read<T: Read>(io: &mut T) -> std::io::Result<u64> {
+    let mut value = 0u64;
+    let mut byte = [0u8; 1];
+    for shift in (0..64).step_by(7) {
+        io.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        "ULEB128 value too long to fit into u64",
+    ))
+}